@@ -0,0 +1,442 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+use crate::geometry::{RingParameters, RingType};
+use crate::stl_output::generate_stl_file;
+
+/// CSV columns of a job file, with the names they may go by
+const CSV_COLUMNS: [(&str, &[&str]); 5] = [
+    ("name", &["name"]),
+    ("ring_type", &["ring_type", "type"]),
+    ("outer_diameter", &["outer_diameter", "od"]),
+    ("inner_diameter", &["inner_diameter", "id"]),
+    ("height", &["height", "h"]),
+];
+
+/// One ring of a batch job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchRow {
+    /// File name without extension; the ring's own file name when unset
+    pub name: Option<String>,
+    #[serde(alias = "type")]
+    pub ring_type: String,
+    #[serde(alias = "od")]
+    pub outer_diameter: f32,
+    #[serde(alias = "id")]
+    pub inner_diameter: f32,
+    #[serde(alias = "h")]
+    pub height: Option<f32>,
+}
+
+impl BatchRow {
+    pub fn to_parameters(&self) -> Result<RingParameters> {
+        let ring_type = self.ring_type.parse::<RingType>()?;
+        let mut params = RingParameters::with_defaults(ring_type, self.outer_diameter, self.inner_diameter);
+        if let Some(height) = self.height {
+            params = params.with_height(height);
+        }
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+/// Outcome of one row of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRowReport {
+    /// Position of the ring in the job, from 1
+    pub row: usize,
+    pub name: Option<String>,
+    pub success: bool,
+    pub file_path: Option<String>,
+    pub triangle_count: Option<usize>,
+    pub error: Option<String>,
+}
+
+/// Per-row results of a batch; a bad row does not stop the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// The job was cancelled before every ring was generated
+    pub cancelled: bool,
+    pub rows: Vec<BatchRowReport>,
+}
+
+/// Progress of a running job, reported after each ring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    /// Rings finished, including those that failed
+    pub rings_done: usize,
+    pub rings_total: usize,
+    pub triangles_written: usize,
+}
+
+/// Shared flag that asks a running job to stop; clones share the flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Layout of a job file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobFormat {
+    /// A header line naming the columns, then one ring per line
+    Csv,
+    /// An array of ring objects
+    Json,
+}
+
+impl JobFormat {
+    /// Format from the file extension, or from the content for other extensions
+    pub fn detect(path: &Path, text: &str) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("csv") => JobFormat::Csv,
+            Some("json") => JobFormat::Json,
+            _ if text.trim_start().starts_with('[') => JobFormat::Json,
+            _ => JobFormat::Csv,
+        }
+    }
+}
+
+/// Rings of a job file. Rows that cannot be read are kept as errors so they show
+/// up in the report; only a file that cannot be read at all is an error.
+pub fn parse_job(text: &str, format: JobFormat) -> Result<Vec<Result<BatchRow, String>>> {
+    match format {
+        JobFormat::Csv => parse_csv_job(text),
+        JobFormat::Json => {
+            let rows: Vec<serde_json::Value> = serde_json::from_str(text)
+                .map_err(|e| anyhow!("Job file is not a JSON array of rings: {}", e))?;
+            Ok(rows
+                .into_iter()
+                .map(|row| serde_json::from_value(row).map_err(|e| e.to_string()))
+                .collect())
+        }
+    }
+}
+
+fn parse_csv_job(text: &str) -> Result<Vec<Result<BatchRow, String>>> {
+    // Blank lines and # comments are skipped
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+    let header = lines.next().ok_or_else(|| anyhow!("Job file is empty"))?;
+
+    let mut columns = Vec::new();
+    for title in csv_fields(header)? {
+        let title = title.trim().to_lowercase();
+        let column = CSV_COLUMNS
+            .iter()
+            .find(|(_, names)| names.contains(&title.as_str()))
+            .map(|(column, _)| *column)
+            .ok_or_else(|| anyhow!("Unknown column '{}' in the job file header", title))?;
+        columns.push(column);
+    }
+    for required in ["ring_type", "outer_diameter", "inner_diameter"] {
+        if !columns.contains(&required) {
+            bail!("Job file header has no {} column", required);
+        }
+    }
+
+    Ok(lines.map(|line| csv_row(&columns, line)).collect())
+}
+
+fn csv_row(columns: &[&str], line: &str) -> Result<BatchRow, String> {
+    let fields = csv_fields(line).map_err(|e| e.to_string())?;
+    if fields.len() != columns.len() {
+        return Err(format!("Expected {} fields, found {}", columns.len(), fields.len()));
+    }
+
+    let mut row = BatchRow {
+        name: None,
+        ring_type: String::new(),
+        outer_diameter: f32::NAN,
+        inner_diameter: f32::NAN,
+        height: None,
+    };
+    for (column, field) in columns.iter().zip(&fields) {
+        let field = field.trim();
+        if field.is_empty() {
+            continue;
+        }
+        let number = || field.parse::<f32>().map_err(|_| format!("Invalid {}: '{}'", column, field));
+        match *column {
+            "name" => row.name = Some(field.to_string()),
+            "ring_type" => row.ring_type = field.to_string(),
+            "outer_diameter" => row.outer_diameter = number()?,
+            "inner_diameter" => row.inner_diameter = number()?,
+            _ => row.height = Some(number()?),
+        }
+    }
+    if row.ring_type.is_empty() || row.outer_diameter.is_nan() || row.inner_diameter.is_nan() {
+        return Err("Ring type, outer and inner diameter are required".to_string());
+    }
+    Ok(row)
+}
+
+/// Fields of a CSV line; fields in double quotes may hold commas and "" for a quote
+fn csv_fields(line: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        bail!("Unclosed quote");
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Generate every ring of a job as an STL file in the output directory, on one
+/// worker thread per core. `progress` is called on this thread after each ring.
+/// Once `cancel` is set no further ring is started, and the rows left over are
+/// reported as cancelled.
+pub fn run_batch(
+    rows: Vec<Result<BatchRow, String>>,
+    output_dir: Option<&str>,
+    cancel: &CancelToken,
+    progress: impl FnMut(BatchProgress),
+) -> BatchReport {
+    let names: Vec<Option<String>> = rows.iter()
+        .map(|row| row.as_ref().ok().and_then(|r| r.name.clone()))
+        .collect();
+    let plans = plan_batch(rows);
+
+    let results = run_parallel(plans.len(), cancel, progress, |index| match &plans[index] {
+        Ok((params, file_stem)) => generate_stl_file(params, output_dir, Some(file_stem))
+            .map_err(|e| format!("Failed to generate ring: {}", e)),
+        Err(e) => Err(e.clone()),
+    });
+    batch_report(results, names, cancel)
+}
+
+/// Run `work` for every index on one worker thread per core. Work returns its
+/// result and the triangles it wrote; indices are handed out in order, and
+/// those not started before `cancel` was set come back as cancelled.
+pub(crate) fn run_parallel<T: Send>(
+    count: usize,
+    cancel: &CancelToken,
+    mut progress: impl FnMut(BatchProgress),
+    work: impl Fn(usize) -> Result<(T, usize), String> + Sync,
+) -> Vec<Result<(T, usize), String>> {
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get()).min(count.max(1));
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<(T, usize), String>>> = (0..count).map(|_| None).collect();
+    let mut state = BatchProgress { rings_done: 0, rings_total: count, triangles_written: 0 };
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= count {
+                    break;
+                }
+                let result = match cancel.is_cancelled() {
+                    true => Err("Cancelled".to_string()),
+                    false => work(index),
+                };
+                if sender.send((index, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Results come back as they finish
+        for (index, result) in receiver {
+            state.rings_done += 1;
+            if let Ok((_, triangles)) = &result {
+                state.triangles_written += triangles;
+            }
+            results[index] = Some(result);
+            progress(state.clone());
+        }
+    });
+
+    results.into_iter()
+        .map(|result| result.unwrap_or_else(|| Err("Cancelled".to_string())))
+        .collect()
+}
+
+/// Report of finished work: the file written and its triangle count, per row
+pub(crate) fn batch_report(
+    results: Vec<Result<(String, usize), String>>,
+    names: Vec<Option<String>>,
+    cancel: &CancelToken,
+) -> BatchReport {
+    let rows: Vec<BatchRowReport> = results.into_iter().zip(names).enumerate()
+        .map(|(index, (result, name))| {
+            let row = index + 1;
+            match result {
+                Ok((path, triangles)) => BatchRowReport {
+                    row, name, success: true, file_path: Some(path), triangle_count: Some(triangles), error: None,
+                },
+                Err(e) => BatchRowReport {
+                    row, name, success: false, file_path: None, triangle_count: None, error: Some(e),
+                },
+            }
+        })
+        .collect();
+    let succeeded = rows.iter().filter(|r| r.success).count();
+    BatchReport {
+        succeeded,
+        failed: rows.len() - succeeded,
+        cancelled: cancel.is_cancelled(),
+        rows,
+    }
+}
+
+/// Parameters and file stem of every row, checked before anything is generated.
+/// Two rows writing the same file would silently lose the first ring.
+fn plan_batch(rows: Vec<Result<BatchRow, String>>) -> Vec<Result<(RingParameters, String), String>> {
+    let mut file_stems: HashMap<String, usize> = HashMap::new();
+    rows.into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let row_number = index + 1;
+            let row = row?;
+            let params = row.to_parameters().map_err(|e| format!("Invalid parameters: {}", e))?;
+            let file_stem = match &row.name {
+                Some(name) => valid_file_stem(name)?,
+                None => params.file_stem(),
+            };
+            if let Some(first) = file_stems.get(&file_stem) {
+                return Err(format!("Same file name as row {}: {}", first, file_stem));
+            }
+            file_stems.insert(file_stem.clone(), row_number);
+            Ok((params, file_stem))
+        })
+        .collect()
+}
+
+/// Read a CSV or JSON job file and generate its rings
+pub fn run_job_file(
+    path: &str,
+    output_dir: Option<&str>,
+    cancel: &CancelToken,
+    progress: impl FnMut(BatchProgress),
+) -> Result<BatchReport> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    let rows = parse_job(&text, JobFormat::detect(Path::new(path), &text))?;
+    Ok(run_batch(rows, output_dir, cancel, progress))
+}
+
+/// A row name is used as the file stem, so it must stay inside the output directory
+fn valid_file_stem(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', ':']) {
+        return Err(format!("Invalid name '{}': it must be a plain file name", name));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(text: &str, format: JobFormat) -> Vec<Result<BatchRow, String>> {
+        parse_job(text, format).unwrap()
+    }
+
+    #[test]
+    fn csv_header_takes_any_column_name_and_order() {
+        let text = "# rings for the 2\" mount\nH, OD, Type, ID, Name\n\n22,50,CX,40,\"front, left\"\n,60,cc,50,\n";
+        let rows = rows(text, JobFormat::Csv);
+        assert_eq!(rows.len(), 2);
+
+        let first = rows[0].as_ref().unwrap();
+        assert_eq!(first.name.as_deref(), Some("front, left"));
+        assert_eq!((first.ring_type.as_str(), first.outer_diameter, first.inner_diameter), ("CX", 50.0, 40.0));
+        assert_eq!(first.height, Some(22.0));
+
+        let second = rows[1].as_ref().unwrap();
+        assert_eq!((second.name.as_deref(), second.height), (None, None));
+        assert_eq!(second.to_parameters().unwrap().ring_type, RingType::Concave);
+    }
+
+    #[test]
+    fn bad_csv_rows_are_reported_without_stopping_the_others() {
+        let text = "type,od,id\nCX,50,40\nCX,50\nCX,fifty,40\n,50,40\nCX,\"50,40\nCC,60,50";
+        let rows = rows(text, JobFormat::Csv);
+        let errors: Vec<Option<&str>> = rows.iter().map(|row| row.as_ref().err().map(String::as_str)).collect();
+        assert_eq!(errors, [
+            None,
+            Some("Expected 3 fields, found 2"),
+            Some("Invalid outer_diameter: 'fifty'"),
+            Some("Ring type, outer and inner diameter are required"),
+            Some("Unclosed quote"),
+            None,
+        ]);
+    }
+
+    #[test]
+    fn csv_header_errors_fail_the_whole_job() {
+        let error = |text| parse_job(text, JobFormat::Csv).unwrap_err().to_string();
+        assert_eq!(error("\n# nothing\n"), "Job file is empty");
+        assert_eq!(error("type,od,id,colour\n"), "Unknown column 'colour' in the job file header");
+        assert_eq!(error("type,od,h\nCX,50,22\n"), "Job file header has no inner_diameter column");
+    }
+
+    #[test]
+    fn json_rows_take_aliases_and_fail_one_by_one() {
+        let text = r#"[
+            {"name": "a", "type": "3P", "od": 50, "id": 40, "h": 10},
+            {"ring_type": "CX", "outer_diameter": 50, "inner_diameter": 40, "colour": "red"},
+            {"ring_type": "CX", "outer_diameter": 50}
+        ]"#;
+        let rows = rows(text, JobFormat::Json);
+        let first = rows[0].as_ref().unwrap();
+        assert_eq!((first.ring_type.as_str(), first.height), ("3P", Some(10.0)));
+        assert!(rows[1].as_ref().unwrap_err().contains("unknown field `colour`"));
+        assert!(rows[2].as_ref().unwrap_err().contains("missing field `inner_diameter`"));
+
+        let error = parse_job(r#"{"ring_type": "CX"}"#, JobFormat::Json).unwrap_err();
+        assert!(error.to_string().starts_with("Job file is not a JSON array of rings"), "{}", error);
+    }
+
+    #[test]
+    fn job_format_follows_the_extension_then_the_content() {
+        assert_eq!(JobFormat::detect(Path::new("rings.CSV"), "[]"), JobFormat::Csv);
+        assert_eq!(JobFormat::detect(Path::new("rings.json"), "type,od,id"), JobFormat::Json);
+        assert_eq!(JobFormat::detect(Path::new("rings.txt"), "  [{}]"), JobFormat::Json);
+        assert_eq!(JobFormat::detect(Path::new("rings"), "type,od,id"), JobFormat::Csv);
+    }
+
+    #[test]
+    fn plan_reports_invalid_rings_and_names() {
+        let text = "name,type,od,id\na,CX,50,40\na,CC,60,50\n../b,CX,50,40\nc,CX,40,50\n,CX,50,40\n";
+        let plan = plan_batch(rows(text, JobFormat::Csv));
+        assert_eq!(plan[0].as_ref().unwrap().1, "a");
+        assert_eq!(plan[1].as_ref().unwrap_err(), "Same file name as row 1: a");
+        assert!(plan[2].as_ref().unwrap_err().starts_with("Invalid name '../b'"));
+        assert!(plan[3].as_ref().unwrap_err().starts_with("Invalid parameters:"));
+        assert_eq!(plan[4].as_ref().unwrap().1, RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap().file_stem());
+    }
+}
@@ -0,0 +1,452 @@
+//! Headless front end to the ring generator for build servers and lab scripts.
+//! Every command prints one JSON document on stdout.
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::ExitCode;
+
+use optics_ring_generator_lib::batch::{run_job_file, BatchProgress, BatchReport, CancelToken};
+use optics_ring_generator_lib::commands::{generate_ring, read_ring_parameters, read_ring_spec};
+use optics_ring_generator_lib::geometry::{generate_ring_mesh, RingParameters};
+use optics_ring_generator_lib::server::{preview_report, serve, validation_report, DEFAULT_PORT};
+use optics_ring_generator_lib::spec::{spec_version, RingSpec, SpecFormat, SPEC_EXTENSION, SPEC_VERSION};
+use optics_ring_generator_lib::sweep::{run_sweep, Sweep, SweepAxis, SweepLayout};
+use optics_ring_generator_lib::RingRequest;
+use serde_json::{json, Map, Value};
+
+/// Exit code when the ring could not be generated or written
+const EXIT_FAILURE: u8 = 1;
+/// Exit code for invalid parameters and usage errors
+const EXIT_INVALID: u8 = 2;
+
+const USAGE: &str = "\
+Usage: optics-ring-cli <command> [options]
+
+Commands:
+  generate        Write the ring file (and a drawing with --drawing svg|pdf)
+  preview-stats   Segment count, mesh statistics and mesh check, nothing is written
+  validate        Check the parameters and the printability of the ring; exits
+                  with 1 if the mesh check fails or the ring is not printable
+  batch FILE      Write an STL file for every ring of a CSV or JSON job file
+                  (--output-path DIR); exits with 1 if any row failed
+  sweep           A family of rings with one or two fields swept over a range:
+                  --sweep inner-diameter=24.8:25.4:0.1 (once or twice) and
+                  --layout files|plate for separate files or one labelled plate STL
+  info            Derived names and metadata of a ring; give an STL file written
+                  by this tool or a spec file instead of options to read it back
+  save-spec [FILE]
+                  Save the ring and export settings as a versioned spec file,
+                  TOML unless FILE ends in .json (default <part name>.ring.toml);
+                  --name, --description and --author fill in its metadata
+  migrate-spec FILE [NEW]
+                  Rewrite a spec of an older version as the current version
+  serve           Answer HTTP requests on localhost (--port, default 8471; --host to
+                  listen elsewhere): GET /ring-types, and POST a JSON request to
+                  /generate for the ring file, /preview-stats or /validate
+  help            Show this message
+
+Options are the fields of a ring request in kebab case, e.g.
+  --ring-type CX --outer-diameter 60 --inner-diameter 50 --height 5
+  --lens-radius 80 --seat-type toroidal --format 3mf --output-path out/
+  --request FILE  Read the request from a JSON file; options override its fields
+  --spec FILE     Read the ring from a .ring.toml or JSON spec file, the same way
+
+Exit codes: 0 success, 1 generation or write failure, 2 invalid parameters.
+";
+
+/// Why a command failed, and the exit code that reports it
+struct Failure {
+    code: u8,
+    message: String,
+    /// Printed instead of the message when the command has a full report
+    output: Option<Value>,
+}
+
+impl Failure {
+    fn invalid(message: impl Into<String>) -> Self {
+        Failure { code: EXIT_INVALID, message: message.into(), output: None }
+    }
+
+    fn failed(message: impl Into<String>) -> Self {
+        Failure { code: EXIT_FAILURE, message: message.into(), output: None }
+    }
+
+    fn report(output: Value) -> Self {
+        let message = output["message"].as_str().unwrap_or_default().to_string();
+        Failure { code: EXIT_FAILURE, message, output: Some(output) }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprint!("{}", USAGE);
+        return ExitCode::from(EXIT_INVALID);
+    };
+
+    let result = match command.as_str() {
+        "generate" => parse_request(rest).and_then(generate),
+        "preview-stats" => parse_request(rest).and_then(preview_stats),
+        "validate" => parse_request(rest).and_then(validate),
+        "info" => info(rest),
+        "batch" => batch(rest),
+        "sweep" => sweep(rest),
+        "save-spec" => save_spec(rest),
+        "migrate-spec" => migrate_spec(rest),
+        "serve" => serve_http(rest),
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        other => Err(Failure::invalid(format!("Unknown command '{}', see `optics-ring-cli help`", other))),
+    };
+
+    match result {
+        Ok(output) => {
+            print_json(&output);
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            let output = failure.output
+                .unwrap_or_else(|| json!({ "success": false, "message": failure.message }));
+            print_json(&output);
+            ExitCode::from(failure.code)
+        }
+    }
+}
+
+/// Print the result; a closed pipe is the reader's choice, not an error
+fn print_json(output: &Value) {
+    let text = serde_json::to_string_pretty(output).unwrap_or_default();
+    let _ = writeln!(std::io::stdout(), "{}", text);
+}
+
+/// Build a ring request from `--field value` options. Field names are those of
+/// the request with dashes; values are passed as text and the request reads
+/// numbers from its numeric fields.
+fn parse_request(args: &[String]) -> Result<RingRequest, Failure> {
+    let (fields, positional) = parse_options(args)?;
+    if let Some(arg) = positional.first() {
+        return Err(Failure::invalid(format!("Unexpected argument '{}'", arg)));
+    }
+    request_from_fields(fields)
+}
+
+fn request_from_fields(fields: Map<String, Value>) -> Result<RingRequest, Failure> {
+    serde_json::from_value(Value::Object(fields))
+        .map_err(|e| Failure::invalid(format!("Invalid request: {}", e)))
+}
+
+/// Split the arguments into request fields and positional arguments
+fn parse_options(args: &[String]) -> Result<(Map<String, Value>, Vec<String>), Failure> {
+    let mut fields = Map::new();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(option) = arg.strip_prefix("--") else {
+            positional.push(arg.clone());
+            continue;
+        };
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, value.to_string()),
+            None => {
+                let value = args.next()
+                    .ok_or_else(|| Failure::invalid(format!("Missing value for --{}", option)))?;
+                (option, value.clone())
+            }
+        };
+        if name == "sweep" {
+            // Given once or twice, for one or two swept fields
+            let sweeps = fields.entry("sweep").or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(sweeps) = sweeps {
+                sweeps.push(Value::from(value));
+            }
+        } else if name == "request" || name == "spec" {
+            // Options given so far stay on top of the file's fields
+            let base = if name == "spec" { spec_fields(&value)? } else { request_fields(&value)? };
+            for (key, value) in base {
+                fields.entry(key).or_insert(value);
+            }
+        } else {
+            fields.insert(name.replace('-', "_"), Value::from(value));
+        }
+    }
+    Ok((fields, positional))
+}
+
+fn request_fields(path: &str) -> Result<Map<String, Value>, Failure> {
+    let file = std::fs::read_to_string(path)
+        .map_err(|e| Failure::invalid(format!("Failed to read {}: {}", path, e)))?;
+    match serde_json::from_str(&file) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(Failure::invalid(format!("Request file {} is not a JSON object", path))),
+        Err(e) => Err(Failure::invalid(format!("Invalid request file {}: {}", path, e))),
+    }
+}
+
+fn spec_fields(path: &str) -> Result<Map<String, Value>, Failure> {
+    let request = read_ring_spec(path.to_string()).map_err(Failure::invalid)?;
+    match serde_json::to_value(request) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        _ => Err(Failure::failed(format!("Failed to read the request of {}", path))),
+    }
+}
+
+/// Remove an option that is not a request field, as text
+fn take_text(fields: &mut Map<String, Value>, name: &str) -> Option<String> {
+    fields.remove(name).map(|value| match value {
+        Value::String(text) => text,
+        other => other.to_string(),
+    })
+}
+
+/// Parse the request completely, so that bad options exit as invalid
+/// parameters before any mesh is generated
+fn parameters(request: &RingRequest) -> Result<RingParameters, Failure> {
+    let params = request.to_parameters().map_err(Failure::invalid)?;
+    request.output_format().map_err(Failure::invalid)?;
+    request.drawing_format().map_err(Failure::invalid)?;
+    Ok(params)
+}
+
+fn generate(request: RingRequest) -> Result<Value, Failure> {
+    parameters(&request)?;
+    let response = generate_ring(request, &CancelToken::default());
+    let output = serde_json::to_value(&response).map_err(|e| Failure::failed(e.to_string()))?;
+    if output["success"] == true {
+        Ok(output)
+    } else {
+        Err(Failure::report(output))
+    }
+}
+
+fn preview_stats(request: RingRequest) -> Result<Value, Failure> {
+    let params = parameters(&request)?;
+    let mesh = generate_ring_mesh(&params)
+        .map_err(|e| Failure::failed(format!("Failed to generate mesh: {}", e)))?;
+
+    Ok(preview_report(&params, &mesh))
+}
+
+fn validate(request: RingRequest) -> Result<Value, Failure> {
+    let params = parameters(&request)?;
+    let mesh = generate_ring_mesh(&params)
+        .map_err(|e| Failure::failed(format!("Failed to generate mesh: {}", e)))?;
+
+    // A ring that fails its checks fails the command, with the full report
+    let mut report = validation_report(&params, &mesh);
+    let message = match (report["valid"] == true, report["printable"] == true) {
+        (true, true) => return Ok(report),
+        (false, _) => "The mesh check found problems with the ring",
+        (true, false) => "The ring is not printable",
+    };
+    report["success"] = Value::from(false);
+    report["message"] = Value::from(message);
+    Err(Failure::report(report))
+}
+
+fn info(args: &[String]) -> Result<Value, Failure> {
+    let (fields, positional) = parse_options(args)?;
+    let request = match positional.as_slice() {
+        [] => request_from_fields(fields)?,
+        [path] if fields.is_empty() && is_spec_file(path) => read_ring_spec(path.clone()).map_err(Failure::invalid)?,
+        [path] if fields.is_empty() => read_ring_parameters(path.clone()).map_err(Failure::invalid)?,
+        [_] => return Err(Failure::invalid("Give either a file or ring options, not both")),
+        [_, extra, ..] => return Err(Failure::invalid(format!("Unexpected argument '{}'", extra))),
+    };
+    let params = parameters(&request)?;
+    let format = request.output_format().map_err(Failure::invalid)?;
+    let metadata: Map<String, Value> = params.metadata()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.into()))
+        .collect();
+
+    Ok(json!({
+        "success": true,
+        "ring_type": params.type_code(),
+        "part_name": params.file_stem(),
+        "filename": params.filename(format.extension()),
+        "segments": params.segment_count(),
+        "contact_diameter": params.seat_layout().map(|layout| layout.contact_radius * 2.0),
+        "metadata": metadata,
+        "request": request,
+    }))
+}
+
+fn batch(args: &[String]) -> Result<Value, Failure> {
+    let (mut fields, positional) = parse_options(args)?;
+    let output_dir = take_text(&mut fields, "output_path");
+    if let Some(name) = fields.keys().next() {
+        return Err(Failure::invalid(format!("Unknown option --{} for batch", name.replace('_', "-"))));
+    }
+    let [path] = positional.as_slice() else {
+        return Err(Failure::invalid("Give exactly one job file"));
+    };
+
+    let report = run_job_file(path, output_dir.as_deref(), &CancelToken::default(), print_progress)
+        .map_err(|e| Failure::invalid(e.to_string()))?;
+    report_output(report)
+}
+
+fn sweep(args: &[String]) -> Result<Value, Failure> {
+    let (mut fields, positional) = parse_options(args)?;
+    if let Some(arg) = positional.first() {
+        return Err(Failure::invalid(format!("Unexpected argument '{}'", arg)));
+    }
+    let axes = match fields.remove("sweep") {
+        Some(Value::Array(axes)) => axes.iter()
+            .map(|axis| axis.as_str().unwrap_or_default().parse::<SweepAxis>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Failure::invalid(e.to_string()))?,
+        _ => return Err(Failure::invalid("Give --sweep field=start:stop:step once or twice")),
+    };
+    let layout = match fields.remove("layout") {
+        Some(layout) => layout.as_str().unwrap_or_default().parse::<SweepLayout>()
+            .map_err(|e| Failure::invalid(e.to_string()))?,
+        None => SweepLayout::default(),
+    };
+    let request = request_from_fields(fields)?;
+    let sweep = Sweep { base: parameters(&request)?, axes };
+    let format = request.output_format().map_err(Failure::invalid)?;
+
+    let report = run_sweep(&sweep, layout, format, request.output_path(), &CancelToken::default(), print_progress)
+        .map_err(|e| Failure::invalid(e.to_string()))?;
+    report_output(report)
+}
+
+/// Spec files are TOML or JSON; anything else is read as an STL file
+fn is_spec_file(path: &str) -> bool {
+    let path = path.to_lowercase();
+    path.ends_with(".toml") || path.ends_with(".json")
+}
+
+fn save_spec(args: &[String]) -> Result<Value, Failure> {
+    let (mut fields, positional) = parse_options(args)?;
+    let name = take_text(&mut fields, "name");
+    let description = take_text(&mut fields, "description");
+    let author = take_text(&mut fields, "author");
+    let request = request_from_fields(fields)?;
+    parameters(&request)?;
+
+    let mut spec = RingSpec::from_request(&request).map_err(|e| Failure::invalid(e.to_string()))?;
+    spec.metadata.name = name;
+    spec.metadata.description = description;
+    spec.metadata.author = author;
+    let path = match positional.as_slice() {
+        [] => format!("{}{}", spec.ring.file_stem(), SPEC_EXTENSION),
+        [path] => path.clone(),
+        [_, extra, ..] => return Err(Failure::invalid(format!("Unexpected argument '{}'", extra))),
+    };
+    spec.save(Path::new(&path)).map_err(|e| Failure::failed(e.to_string()))?;
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Saved {}", path),
+        "file_path": path,
+        "version": SPEC_VERSION,
+    }))
+}
+
+fn migrate_spec(args: &[String]) -> Result<Value, Failure> {
+    let (fields, positional) = parse_options(args)?;
+    if let Some(name) = fields.keys().next() {
+        return Err(Failure::invalid(format!("Unknown option --{} for migrate-spec", name.replace('_', "-"))));
+    }
+    let (from, to) = match positional.as_slice() {
+        [from] => (from, from),
+        [from, to] => (from, to),
+        _ => return Err(Failure::invalid("Give the spec file, and optionally a new file to write")),
+    };
+
+    let text = std::fs::read_to_string(from)
+        .map_err(|e| Failure::invalid(format!("Failed to read {}: {}", from, e)))?;
+    let format = SpecFormat::for_path(Path::new(from));
+    let version = spec_version(&text, format).map_err(|e| Failure::invalid(e.to_string()))?;
+    let spec = RingSpec::parse(&text, format).map_err(|e| Failure::invalid(e.to_string()))?;
+    spec.save(Path::new(to)).map_err(|e| Failure::failed(e.to_string()))?;
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Migrated {} from version {} to version {}", from, version, SPEC_VERSION),
+        "file_path": to,
+        "from_version": version,
+        "version": SPEC_VERSION,
+    }))
+}
+
+fn serve_http(args: &[String]) -> Result<Value, Failure> {
+    let (mut fields, positional) = parse_options(args)?;
+    if let Some(arg) = positional.first() {
+        return Err(Failure::invalid(format!("Unexpected argument '{}'", arg)));
+    }
+    let host = take_text(&mut fields, "host").unwrap_or_else(|| "127.0.0.1".to_string());
+    let port = match fields.remove("port") {
+        Some(port) => port.as_str()
+            .and_then(|port| port.parse::<u16>().ok())
+            .ok_or_else(|| Failure::invalid(format!("Invalid port {}", port)))?,
+        None => DEFAULT_PORT,
+    };
+    if let Some(name) = fields.keys().next() {
+        return Err(Failure::invalid(format!("Unknown option --{} for serve", name.replace('_', "-"))));
+    }
+
+    let listener = TcpListener::bind((host.as_str(), port))
+        .map_err(|e| Failure::failed(format!("Failed to listen on {}:{}: {}", host, port, e)))?;
+    serve(listener).map_err(|e| Failure::failed(e.to_string()))?;
+    Ok(json!({ "success": true, "message": "Server stopped" }))
+}
+
+fn print_progress(progress: BatchProgress) {
+    eprintln!("{}/{} rings", progress.rings_done, progress.rings_total);
+}
+
+/// Batch and sweep reports; any failed ring makes the command fail
+fn report_output(report: BatchReport) -> Result<Value, Failure> {
+    let message = format!("Generated {} of {} rings", report.succeeded, report.rows.len());
+    let failed = report.failed;
+    let mut output = serde_json::to_value(report).map_err(|e| Failure::failed(e.to_string()))?;
+    output["success"] = Value::from(failed == 0);
+    output["message"] = Value::from(message);
+    if failed == 0 {
+        Ok(output)
+    } else {
+        Err(Failure::report(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn numeric_looking_text_options_stay_text() {
+        let request = parse_request(&args(
+            "--ring-type CX --outer-diameter 50 --inner-diameter=40.5 --output-path 2024 --format stl",
+        ))
+        .unwrap_or_else(|failure| panic!("{}", failure.message));
+        assert_eq!(request.output_path(), Some("2024"));
+
+        let params = parameters(&request).unwrap_or_else(|failure| panic!("{}", failure.message));
+        assert_eq!((params.outer_diameter, params.inner_diameter), (50.0, 40.5));
+
+        let (mut fields, _) =
+            parse_options(&args("--name 1e5 --author 007")).unwrap_or_else(|failure| panic!("{}", failure.message));
+        assert_eq!(take_text(&mut fields, "name").as_deref(), Some("1e5"));
+        assert_eq!(take_text(&mut fields, "author").as_deref(), Some("007"));
+    }
+
+    #[test]
+    fn numeric_options_must_be_numbers() {
+        for bad in ["--outer-diameter fifty", "--outer-diameter inf", "--contact-count 3.5"] {
+            let options = format!("--ring-type 3P --outer-diameter 50 --inner-diameter 40 {}", bad);
+            let failure = parse_request(&args(&options)).expect_err(bad);
+            assert_eq!(failure.code, EXIT_INVALID);
+            assert!(failure.message.contains("invalid number"), "{}: {}", bad, failure.message);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// Commands behind the frontend; the command line tool calls them directly
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::batch::CancelToken;
+use crate::geometry;
+use crate::manufacturing::{analyze_for_printing, ManufacturingReport};
+use crate::mesh_check::check_mesh;
+use crate::mesh_stats::{compute_mesh_stats, MeshStats};
+use crate::spec::RingSpec;
+use crate::stl_output::{read_stl_parameters, save_drawing, save_mesh_file};
+use crate::{RingRequest, RingResponse};
+
+#[derive(Serialize, Deserialize)]
+pub struct MeshData {
+    vertices: Vec<f32>,  // Flattened vertex data [x,y,z,x,y,z,...]
+    triangles: Vec<u32>, // Flattened triangle indices [i1,i2,i3,i1,i2,i3,...]
+    normals: Option<Vec<f32>>, // Flattened per-vertex normals, when the mesh has them
+    vertex_count: usize,
+    triangle_count: usize,
+}
+
+/// Generate a ring and save its files. Once `cancel` is set nothing more is
+/// written; the async command in `jobs` runs this on a worker thread.
+pub fn generate_ring(request: RingRequest, cancel: &CancelToken) -> RingResponse {
+    // Parse ring type, parameters and output format
+    let parsed = request.to_parameters()
+        .and_then(|p| Ok((p, request.output_format()?, request.drawing_format()?)));
+    let (params, format, drawing_format) = match parsed {
+        Ok(p) => p,
+        Err(e) => return RingResponse {
+            success: false,
+            message: e,
+            filename: None,
+            file_path: None,
+            mesh_check: None,
+            contact_diameter: None,
+            manufacturing: None,
+            drawing_path: None,
+        }
+    };
+    let ring_type = params.type_code();
+
+    // Generate the mesh and check it before anything is written
+    let mesh = match geometry::generate_ring_mesh(&params) {
+        Ok(m) => m,
+        Err(e) => return RingResponse {
+            success: false,
+            message: format!("Failed to generate mesh: {}", e),
+            filename: None,
+            file_path: None,
+            mesh_check: None,
+            contact_diameter: None,
+            manufacturing: None,
+            drawing_path: None,
+        }
+    };
+    if cancel.is_cancelled() {
+        return RingResponse {
+            success: false,
+            message: "Cancelled".to_string(),
+            filename: None,
+            file_path: None,
+            mesh_check: None,
+            contact_diameter: None,
+            manufacturing: None,
+            drawing_path: None,
+        };
+    }
+    let report = check_mesh(&mesh);
+    let manufacturing = analyze_for_printing(&params, &mesh, &report);
+    let contact_diameter = params.seat_layout().map(|layout| layout.contact_radius * 2.0);
+
+    // Determine output directory
+    let output_dir = request.output_path.as_deref();
+
+    // Write the ring file, then the drawing next to it
+    let saved = save_mesh_file(&mesh, &params, output_dir, format).and_then(|file_path| {
+        let drawing_path = drawing_format
+            .map(|drawing_format| save_drawing(&params, output_dir, drawing_format))
+            .transpose()?;
+        Ok((file_path, drawing_path))
+    });
+    match saved {
+        Ok((file_path, drawing_path)) => RingResponse {
+            success: true,
+            message: if report.is_valid {
+                format!("Successfully generated {} ring", ring_type)
+            } else {
+                format!("Generated {} ring, but the mesh check found problems: {}", ring_type, report.issues.join("; "))
+            },
+            filename: Some(params.filename(format.extension())),
+            file_path: Some(file_path),
+            mesh_check: Some(report),
+            contact_diameter,
+            manufacturing: Some(manufacturing),
+            drawing_path,
+        },
+        Err(e) => RingResponse {
+            success: false,
+            message: format!("Failed to write output files: {}", e),
+            filename: None,
+            file_path: None,
+            mesh_check: Some(report),
+            contact_diameter,
+            manufacturing: Some(manufacturing),
+            drawing_path: None,
+        }
+    }
+}
+
+/// Mesh of the ring for the 3D preview. Like the other commands that build a
+/// mesh it runs off the main thread, so a fine mesh does not freeze the window.
+#[cfg_attr(feature = "gui", tauri::command(async))]
+pub fn generate_mesh_preview(request: RingRequest) -> Result<MeshData, String> {
+    let params = request.to_parameters()?;
+    let mesh = geometry::generate_ring_mesh(&params)
+        .map_err(|e| format!("Failed to generate mesh: {}", e))?;
+
+    // Shared vertices go to Three.js as-is, so each one is sent only once
+    Ok(MeshData {
+        vertex_count: mesh.vertex_count(),
+        triangle_count: mesh.triangle_count(),
+        vertices: mesh.vertices.iter().flatten().copied().collect(),
+        triangles: mesh.indices.iter().flatten().copied().collect(),
+        normals: mesh.normals.as_ref().map(|n| n.iter().flatten().copied().collect()),
+    })
+}
+
+/// Volume, surface area, centroid and bounding box of the ring, measured from its mesh
+#[cfg_attr(feature = "gui", tauri::command(async))]
+pub fn get_mesh_stats(request: RingRequest) -> Result<MeshStats, String> {
+    let params = request.to_parameters()?;
+    let mesh = geometry::generate_ring_mesh(&params)
+        .map_err(|e| format!("Failed to generate mesh: {}", e))?;
+
+    Ok(compute_mesh_stats(&mesh))
+}
+
+/// Printability issues and recommended print settings for a ring, without saving it
+#[cfg_attr(feature = "gui", tauri::command(async))]
+pub fn analyze_ring(request: RingRequest) -> Result<ManufacturingReport, String> {
+    let params = request.to_parameters()?;
+    let mesh = geometry::generate_ring_mesh(&params)
+        .map_err(|e| format!("Failed to generate mesh: {}", e))?;
+
+    Ok(analyze_for_printing(&params, &mesh, &check_mesh(&mesh)))
+}
+
+/// Parameters of a ring read back from an STL file this app wrote, as a request
+/// that regenerates it. Fields left at their defaults are left out.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn read_ring_parameters(path: String) -> Result<RingRequest, String> {
+    let data = std::fs::read(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let params = read_stl_parameters(&data)
+        .map_err(|e| format!("Failed to read ring parameters: {}", e))?;
+
+    Ok(RingRequest::from(&params).without_defaults())
+}
+
+/// Request that regenerates the ring of a `.ring.toml` or JSON spec file,
+/// without the fields left at their defaults
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn read_ring_spec(path: String) -> Result<RingRequest, String> {
+    let spec = RingSpec::load(Path::new(&path))
+        .map_err(|e| e.to_string())?;
+
+    Ok(spec.to_request().without_defaults())
+}
+
+/// Save the ring and export settings of a request as a spec file
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn save_ring_spec(request: RingRequest, path: String, name: Option<String>) -> Result<String, String> {
+    let mut spec = RingSpec::from_request(&request)
+        .map_err(|e| e.to_string())?;
+    spec.metadata.name = name;
+    spec.save(Path::new(&path))
+        .map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
@@ -0,0 +1,1025 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, SQRT_2};
+use std::fmt::Write as _;
+
+use crate::geometry::{
+    arc_sweep, chamfer_run, ring_profile, PadTip, ProfileSegment, RingParameters, RingProfile, RingType,
+    CHAMFER_ANGLE_DEG,
+};
+use crate::timestamp::utc_timestamp;
+use crate::xml::xml_escape;
+
+/// A4 landscape sheet (mm)
+const SHEET_WIDTH: f32 = 297.0;
+const SHEET_HEIGHT: f32 = 210.0;
+/// Distance of the drawing frame from the sheet edge (mm)
+const FRAME_MARGIN: f32 = 10.0;
+/// Largest extent of the part in either view, on the sheet (mm)
+const VIEW_SIZE: f32 = 100.0;
+/// Sheet position of the axis in the section view and of the center of the top view (mm)
+const SECTION_CENTER: Point = (85.0, 95.0);
+const TOP_VIEW_CENTER: Point = (222.0, 85.0);
+/// Standard drawing scales, largest first
+const SCALES: [f32; 11] = [20.0, 10.0, 5.0, 2.0, 1.0, 0.5, 0.2, 0.1, 0.05, 0.02, 0.01];
+
+/// Distance between a view and its first dimension line, and between dimension lines (mm)
+const DIMENSION_SPACING: f32 = 8.0;
+/// Gap between a feature and its extension line, and how far the line runs past the dimension (mm)
+const EXTENSION_GAP: f32 = 1.0;
+const EXTENSION_OVERSHOOT: f32 = 1.5;
+const ARROW_LENGTH: f32 = 3.0;
+const ARROW_WIDTH: f32 = 1.0;
+/// Length of the horizontal shoulder at the end of a leader (mm)
+const LEADER_SHOULDER: f32 = 3.0;
+const HATCH_SPACING: f32 = 2.0;
+const TEXT_SIZE: f32 = 3.5;
+const LABEL_SIZE: f32 = 2.0;
+/// Largest angle of one piece of a flattened arc (radians)
+const MAX_ARC_ANGLE: f32 = PI / 90.0;
+
+/// Points per millimetre in PDF user space
+const PDF_POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// File format of the technical drawing
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DrawingFormat {
+    #[default]
+    Svg,
+    Pdf,
+}
+
+impl std::str::FromStr for DrawingFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(DrawingFormat::Svg),
+            "pdf" => Ok(DrawingFormat::Pdf),
+            _ => Err(anyhow::anyhow!("Invalid drawing format: {}. Valid formats are: svg, pdf", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for DrawingFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl DrawingFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DrawingFormat::Svg => "svg",
+            DrawingFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// Dimensioned technical drawing of the ring on an A4 sheet: a section view,
+/// a top view and a title block with the ring type, part name and date
+pub fn drawing_document(params: &RingParameters, format: DrawingFormat) -> Result<Vec<u8>> {
+    let sheet = layout_drawing(params)?;
+    Ok(match format {
+        DrawingFormat::Svg => sheet.to_svg(&params.file_stem()).into_bytes(),
+        DrawingFormat::Pdf => sheet.to_pdf(&params.file_stem()),
+    })
+}
+
+/// Position on the sheet in millimetres, y pointing down
+type Point = (f32, f32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stroke {
+    /// Outlines of the part
+    Visible,
+    /// Dimensions, leaders and hatching
+    Thin,
+    /// Axes, center lines and cutting planes
+    Center,
+}
+
+impl Stroke {
+    fn width(self) -> f32 {
+        match self {
+            Stroke::Visible => 0.5,
+            Stroke::Thin => 0.25,
+            Stroke::Center => 0.18,
+        }
+    }
+
+    fn dash(self) -> &'static [f32] {
+        match self {
+            Stroke::Center => &[8.0, 1.5, 1.0, 1.5],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+#[derive(Debug, Clone)]
+enum Shape {
+    /// Polyline, optionally closed and filled with a gray level (0 = black)
+    Path { points: Vec<Point>, closed: bool, stroke: Stroke, fill: Option<f32> },
+    Circle { center: Point, radius: f32, stroke: Stroke },
+    /// Text on its baseline, rotated clockwise on the sheet by `angle` degrees
+    Text { at: Point, text: String, size: f32, anchor: Anchor, angle: f32 },
+}
+
+/// Drawing content in sheet coordinates, rendered to SVG or PDF
+#[derive(Debug, Default)]
+struct Sheet {
+    shapes: Vec<Shape>,
+}
+
+/// Maps ring coordinates (radius or x, height or y) onto the sheet
+#[derive(Debug, Clone, Copy)]
+struct View {
+    origin: Point,
+    scale: f32,
+}
+
+impl View {
+    /// Sheet position of a point at radius `x` and height `z` in a section, or at (x, y) from above
+    fn at(&self, x: f32, z: f32) -> Point {
+        (self.origin.0 + x * self.scale, self.origin.1 - z * self.scale)
+    }
+}
+
+fn layout_drawing(params: &RingParameters) -> Result<Sheet> {
+    let outer_radius = params.outer_diameter / 2.0;
+    let scale = SCALES
+        .into_iter()
+        .find(|s| params.outer_diameter * s <= VIEW_SIZE && params.height * s <= VIEW_SIZE)
+        .unwrap_or(SCALES[SCALES.len() - 1]);
+
+    // Point-contact rings are cut through the first pad, so the two halves can differ
+    let (right, left) = match params.ring_type {
+        RingType::ThreePoint => point_contact_sections(params),
+        _ => {
+            let profile = ring_profile(params)?;
+            (profile.clone(), profile)
+        }
+    };
+
+    let mut sheet = Sheet::default();
+    sheet.frame();
+
+    let section = View {
+        origin: (SECTION_CENTER.0, SECTION_CENTER.1 + params.height * scale / 2.0),
+        scale,
+    };
+    draw_section(&mut sheet, &section, params, &right, &left);
+
+    let top = View { origin: TOP_VIEW_CENTER, scale };
+    draw_top_view(&mut sheet, &top, params, &right);
+
+    let section_bottom = section.origin.1 + 2.0 * DIMENSION_SPACING;
+    sheet.text((section.origin.0, section_bottom + 10.0), "SECTION A–A", TEXT_SIZE, Anchor::Middle, 0.0);
+    let top_bottom = top.origin.1 + outer_radius * scale;
+    sheet.text((top.origin.0, top_bottom + 12.0), "TOP VIEW", TEXT_SIZE, Anchor::Middle, 0.0);
+
+    title_block(&mut sheet, params, scale);
+    Ok(sheet)
+}
+
+/// Section through the axis: the profile on the right and its mirror image on
+/// the left, hatched, with the heights, diameters and seat dimensioned
+fn draw_section(sheet: &mut Sheet, view: &View, params: &RingParameters, right: &RingProfile, left: &RingProfile) {
+    let outer = params.outer_diameter / 2.0;
+    let inner = params.inner_diameter / 2.0;
+    let height = params.height;
+    let top = view.at(0.0, height).1;
+    let bottom = view.origin.1;
+
+    for (profile, side) in [(right, 1.0), (left, -1.0)] {
+        let outline: Vec<Point> = profile
+            .to_polyline(MAX_ARC_ANGLE)
+            .into_iter()
+            .map(|(r, z)| view.at(side * r, z))
+            .collect();
+        sheet.hatch(&outline);
+        sheet.path(outline, true, Stroke::Visible, None);
+    }
+    sheet.line((view.origin.0, top - 5.0), (view.origin.0, bottom + 5.0), Stroke::Center);
+
+    // Diameters below, from the lowest corner on each wall, overall height on the right
+    let lowest_at = |radius: f32| {
+        right
+            .edges(0.0)
+            .into_iter()
+            .filter(|(start, _)| (start.0 - radius).abs() < 1e-3)
+            .fold(height, |z, (start, _)| z.min(start.1))
+    };
+    let outer_low = lowest_at(outer);
+    sheet.horizontal_dimension(
+        view.at(-outer, outer_low),
+        view.at(outer, outer_low),
+        bottom + DIMENSION_SPACING,
+        &format!("Ø{}", mm(params.outer_diameter)),
+    );
+    let inner_low = lowest_at(inner);
+    sheet.horizontal_dimension(
+        view.at(-inner, inner_low),
+        view.at(inner, inner_low),
+        bottom + 2.0 * DIMENSION_SPACING,
+        &format!("Ø{}", mm(params.inner_diameter)),
+    );
+    let right_edge = view.at(outer, 0.0).0;
+    let height_column = match params.ring_type {
+        RingType::ThreePoint => right_edge + 2.0 * DIMENSION_SPACING,
+        _ => right_edge + DIMENSION_SPACING,
+    };
+    sheet.vertical_dimension(view.at(outer, 0.0), view.at(outer, height), height_column, &mm(height));
+
+    // Steps and seat are dimensioned inside the bore, from the left half
+    let bore_column = |n: f32| view.at(-inner, 0.0).0 + (n * DIMENSION_SPACING).min(inner * view.scale * n / 3.0);
+    let layout = params.seat_layout();
+
+    match params.ring_type {
+        RingType::Convex => {
+            let step = inner + params.inner_step_width;
+            let middle = inner + params.inner_step_width * 0.5;
+            let top_level = height - params.top_step_height;
+            let middle_level = top_level - params.middle_step_height;
+            let bottom_level = params.bottom_step_height;
+
+            sheet.vertical_dimension(view.at(-step, 0.0), view.at(-inner, bottom_level), bore_column(1.0), &mm(bottom_level));
+            if layout.is_none() {
+                sheet.vertical_dimension(
+                    view.at(-step, top_level),
+                    view.at(-step, height),
+                    bore_column(1.0),
+                    &mm(params.top_step_height),
+                );
+                if params.middle_step_height > 0.0 {
+                    sheet.vertical_dimension(
+                        view.at(-middle, middle_level),
+                        view.at(-middle, top_level),
+                        bore_column(1.0),
+                        &mm(params.middle_step_height),
+                    );
+                }
+            }
+            let step_level = if layout.is_some() { height } else { middle_level };
+            sheet.horizontal_dimension(
+                view.at(inner, step_level),
+                view.at(step, height),
+                top - DIMENSION_SPACING,
+                &mm(params.inner_step_width),
+            );
+
+            if params.chamfer_depth > 0.0 {
+                let run = chamfer_run(params.chamfer_depth);
+                let target = view.at(-(outer - run / 2.0), height - params.chamfer_depth / 2.0);
+                sheet.leader(
+                    target,
+                    (target.0 - 6.0, top - DIMENSION_SPACING),
+                    &format!("{} × {}°", mm(params.chamfer_depth), CHAMFER_ANGLE_DEG),
+                );
+            }
+        }
+        RingType::Concave if layout.is_none() => {
+            // Radius of the dished top
+            let dish = right.edges(0.0).into_iter().find_map(|(start, segment)| match segment {
+                ProfileSegment::Arc { end, center, ccw } => Some((start, end, center, ccw)),
+                ProfileSegment::Line { .. } => None,
+            });
+            if let Some((start, end, center, ccw)) = dish {
+                let radius = ((start.0 - center.0).powi(2) + (start.1 - center.1).powi(2)).sqrt();
+                let angle = (start.1 - center.1).atan2(start.0 - center.0) + arc_sweep(start, end, center, ccw) / 2.0;
+                let target = view.at(center.0 + radius * angle.cos(), center.1 + radius * angle.sin());
+                sheet.leader(target, (target.0 + 4.0, top - DIMENSION_SPACING), &format!("R{}", mm(radius)));
+            }
+        }
+        RingType::Concave => {}
+        RingType::ThreePoint => {
+            let base = height - params.pad_height;
+            sheet.vertical_dimension(
+                view.at(outer, base),
+                view.at(outer, height),
+                right_edge + DIMENSION_SPACING,
+                &mm(params.pad_height),
+            );
+        }
+    }
+
+    // Lens seat: contact line and the radius of the lens it is matched to
+    if let Some(layout) = layout {
+        let contact = (layout.contact_radius, layout.contact_height);
+        let column = match params.ring_type {
+            RingType::Convex => bore_column(2.0),
+            _ => bore_column(1.0),
+        };
+        let base_radius = match params.ring_type {
+            RingType::Convex => inner + params.inner_step_width,
+            _ => inner,
+        };
+        sheet.vertical_dimension(
+            view.at(-base_radius, 0.0),
+            view.at(-contact.0, contact.1),
+            column,
+            &mm(contact.1),
+        );
+        sheet.horizontal_dimension(
+            view.at(-contact.0, contact.1),
+            view.at(contact.0, contact.1),
+            top - 2.0 * DIMENSION_SPACING,
+            &format!("Ø{}", mm(2.0 * contact.0)),
+        );
+
+        let seat = layout.seat;
+        let mut note = format!("SR{} {} seat", mm(seat.radius_of_curvature), seat.seat_type);
+        if let Some(radius) = layout.torus_radius() {
+            let _ = write!(note, " R{}", mm(radius));
+        }
+        let target = view.at(contact.0, contact.1);
+        sheet.leader(target, (target.0 - 6.0, top - 3.0 * DIMENSION_SPACING), &note);
+    }
+}
+
+/// View from above: the edges of the ring as circles, or the base and pads of
+/// a point-contact ring, with the cutting plane of the section
+fn draw_top_view(sheet: &mut Sheet, view: &View, params: &RingParameters, section: &RingProfile) {
+    let outer = params.outer_diameter / 2.0;
+    let inner = params.inner_diameter / 2.0;
+    let reach = outer * view.scale + 4.0;
+    let (cx, cy) = view.origin;
+    sheet.line((cx - reach, cy), (cx + reach, cy), Stroke::Center);
+    sheet.line((cx, cy - reach), (cx, cy + reach), Stroke::Center);
+
+    // Every corner of the profile shows as a circle from above
+    let mut radii: Vec<f32> = match params.ring_type {
+        RingType::ThreePoint => vec![outer, inner],
+        _ => section.edges(0.0).into_iter().map(|(start, _)| start.0).collect(),
+    };
+    radii.sort_by(|a, b| b.total_cmp(a));
+    radii.dedup_by(|a, b| (*a - *b).abs() < 1e-3);
+    for radius in radii {
+        sheet.circle(view.origin, radius * view.scale, Stroke::Visible);
+    }
+
+    let offset = match params.ring_type {
+        RingType::ThreePoint => params.contact_offset_deg.to_radians(),
+        _ => 0.0,
+    };
+    if params.ring_type == RingType::ThreePoint {
+        draw_pads(sheet, view, params);
+    }
+
+    // Cutting plane, with the right half of the section on the side of the angle
+    let direction = (offset.cos(), -offset.sin());
+    let plane = reach;
+    sheet.line(
+        (cx - direction.0 * plane, cy - direction.1 * plane),
+        (cx + direction.0 * plane, cy + direction.1 * plane),
+        Stroke::Center,
+    );
+    for side in [-1.0, 1.0] {
+        let at = (cx + side * direction.0 * (plane + 3.0), cy + side * direction.1 * (plane + 3.0) + TEXT_SIZE / 2.0);
+        sheet.text(at, "A", TEXT_SIZE, Anchor::Middle, 0.0);
+    }
+}
+
+/// Pad outlines from above, the pitch circle and a note on the pads
+fn draw_pads(sheet: &mut Sheet, view: &View, params: &RingParameters) {
+    let outer = params.outer_diameter / 2.0;
+    let inner = params.inner_diameter / 2.0;
+    let pitch = params.pad_center_radius();
+    let pad_radius = params.pad_width / 2.0;
+    let base = params.height - params.pad_height;
+    let tip_center = params.height - pad_radius;
+    let count = params.contact_count;
+    let offset = params.contact_offset_deg.to_radians();
+
+    sheet.circle(view.origin, pitch * view.scale, Stroke::Center);
+
+    // Top view looks down on the XY plane, so angles run counter-clockwise on the sheet
+    let polar = |r: f32, angle: f32| view.at(r * angle.cos(), r * angle.sin());
+    for i in 0..count {
+        let angle = offset + 2.0 * PI * i as f32 / count as f32;
+        let (cos, sin) = (angle.cos(), angle.sin());
+        // Point at `along` the pad's radial line and `across` to its side
+        let local = |along: f32, across: f32| view.at(along * cos - across * sin, along * sin + across * cos);
+        match params.pad_tip {
+            PadTip::Flat => {
+                let half_angle = pad_radius / pitch;
+                let steps = ((2.0 * half_angle / MAX_ARC_ANGLE).ceil() as usize).max(1);
+                let mut outline: Vec<Point> = (0..=steps)
+                    .map(|k| polar(outer, angle - half_angle + 2.0 * half_angle * k as f32 / steps as f32))
+                    .collect();
+                outline.extend((0..=steps).map(|k| polar(inner, angle + half_angle - 2.0 * half_angle * k as f32 / steps as f32)));
+                sheet.path(outline, true, Stroke::Visible, None);
+            }
+            PadTip::CylindricalRidge => {
+                for side in [-1.0, 1.0] {
+                    let across = side * pad_radius;
+                    let from = (inner * inner - across * across).max(0.0).sqrt();
+                    let to = (outer * outer - across * across).max(0.0).sqrt();
+                    sheet.line(local(from, across), local(to, across), Stroke::Visible);
+                }
+                sheet.line(local(inner, 0.0), local(outer, 0.0), Stroke::Center);
+            }
+            PadTip::SphericalBall => {
+                // Outline of the ball, or of the cap where it meets the base
+                let footprint = if tip_center >= base {
+                    pad_radius
+                } else {
+                    (pad_radius * pad_radius - (base - tip_center).powi(2)).max(0.0).sqrt()
+                };
+                sheet.circle(polar(pitch, angle), footprint * view.scale, Stroke::Visible);
+            }
+        }
+    }
+
+    let tip = match params.pad_tip {
+        PadTip::Flat => format!("flat pad {} wide", mm(params.pad_width)),
+        PadTip::CylindricalRidge => format!("ridge pad R{}", mm(pad_radius)),
+        PadTip::SphericalBall => format!("ball pad SR{}", mm(pad_radius)),
+    };
+    // Note on the topmost pad, running back over the view so it stays on the sheet
+    let topmost = (0..count)
+        .map(|i| polar(pitch, offset + 2.0 * PI * i as f32 / count as f32))
+        .fold((0.0, f32::MAX), |best, p| if p.1 < best.1 - 1e-3 { p } else { best });
+    let above = view.origin.1 - outer * view.scale - 6.0;
+    let inward = if topmost.0 > view.origin.0 { -4.0 } else { 4.0 };
+    sheet.leader(
+        topmost,
+        (topmost.0 + inward, above),
+        &format!("{} × {}, {}° pitch", count, tip, mm(360.0 / count as f32)),
+    );
+
+    let on_pitch = polar(pitch, 1.25 * PI);
+    let below = view.origin.1 + outer * view.scale + 4.0;
+    sheet.leader(on_pitch, (on_pitch.0 - 6.0, below), &format!("PCD Ø{}", mm(2.0 * pitch)));
+}
+
+/// Section of a point-contact ring through the first pad and, on the other
+/// side of the axis, half a turn further on
+fn point_contact_sections(params: &RingParameters) -> (RingProfile, RingProfile) {
+    let outer = params.outer_diameter / 2.0;
+    let inner = params.inner_diameter / 2.0;
+    let height = params.height;
+    let base = height - params.pad_height;
+    let pitch = params.pad_center_radius();
+    let pad_radius = params.pad_width / 2.0;
+    let tip_center = height - pad_radius;
+
+    let rectangle = |top: f32| {
+        let mut profile = RingProfile::new(inner, 0.0);
+        profile.line_to(outer, 0.0);
+        profile.line_to(outer, top);
+        profile.line_to(inner, top);
+        profile.close();
+        profile
+    };
+
+    // Flat pads and ridges reach the full height right across the wall at their center
+    let through_pad = match params.pad_tip {
+        PadTip::Flat | PadTip::CylindricalRidge => rectangle(height),
+        PadTip::SphericalBall => {
+            let mut profile = RingProfile::new(inner, 0.0);
+            profile.line_to(outer, 0.0);
+            profile.line_to(outer, base);
+            if tip_center >= base {
+                profile.line_to(pitch + pad_radius, base);
+                profile.line_to(pitch + pad_radius, tip_center);
+                profile.arc_to(pitch - pad_radius, tip_center, (pitch, tip_center), true);
+                profile.line_to(pitch - pad_radius, base);
+            } else {
+                let half_chord = (pad_radius * pad_radius - (base - tip_center).powi(2)).max(0.0).sqrt();
+                profile.line_to(pitch + half_chord, base);
+                profile.arc_to(pitch - half_chord, base, (pitch, tip_center), true);
+            }
+            profile.line_to(inner, base);
+            profile.close();
+            profile
+        }
+    };
+
+    // Half a turn on lands on another pad only for an even number of pads
+    let opposite = if params.contact_count % 2 == 0 { through_pad.clone() } else { rectangle(base) };
+    (through_pad, opposite)
+}
+
+fn title_block(sheet: &mut Sheet, params: &RingParameters, scale: f32) {
+    let (x0, x1) = (SHEET_WIDTH - FRAME_MARGIN - 110.0, SHEET_WIDTH - FRAME_MARGIN);
+    let (y0, y1) = (SHEET_HEIGHT - FRAME_MARGIN - 30.0, SHEET_HEIGHT - FRAME_MARGIN);
+    sheet.path(vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)], true, Stroke::Visible, None);
+
+    let title = match params.ring_type {
+        RingType::Convex => "Convex lens support ring".to_string(),
+        RingType::Concave => "Concave lens support ring".to_string(),
+        RingType::ThreePoint => format!("{}-point contact support ring", params.contact_count),
+    };
+    sheet.text((x0 + 2.0, y0 + 7.0), title, 5.0, Anchor::Start, 0.0);
+
+    let scale_label = if scale >= 1.0 {
+        format!("{}:1", scale)
+    } else {
+        format!("1:{}", (1.0 / scale).round())
+    };
+    let timestamp = utc_timestamp();
+    let rows = [
+        (y0 + 10.0, vec![(70.0, "PART", params.file_stem()), (40.0, "TYPE", params.type_code())]),
+        (
+            y0 + 20.0,
+            vec![
+                (40.0, "DATE", timestamp[..10].to_string()),
+                (35.0, "SCALE", scale_label),
+                (35.0, "UNITS", "mm".to_string()),
+            ],
+        ),
+    ];
+    for (y, cells) in rows {
+        sheet.line((x0, y), (x1, y), Stroke::Thin);
+        let mut x = x0;
+        for (width, label, value) in cells {
+            if x > x0 {
+                sheet.line((x, y), (x, y + 10.0), Stroke::Thin);
+            }
+            sheet.text((x + 1.5, y + 3.0), label, LABEL_SIZE, Anchor::Start, 0.0);
+            sheet.text((x + 1.5, y + 8.0), value, TEXT_SIZE, Anchor::Start, 0.0);
+            x += width;
+        }
+    }
+
+    let mut note = "Dimensions in mm.".to_string();
+    if params.ring_type == RingType::Convex && params.chamfer_depth > 0.0 {
+        let _ = write!(note, " Chamfers at {}° to the wall.", CHAMFER_ANGLE_DEG);
+    }
+    note.push_str(" Optics Ring Generator");
+    sheet.text((FRAME_MARGIN + 4.0, SHEET_HEIGHT - FRAME_MARGIN - 4.0), note, LABEL_SIZE, Anchor::Start, 0.0);
+}
+
+impl Sheet {
+    fn path(&mut self, points: Vec<Point>, closed: bool, stroke: Stroke, fill: Option<f32>) {
+        self.shapes.push(Shape::Path { points, closed, stroke, fill });
+    }
+
+    fn line(&mut self, from: Point, to: Point, stroke: Stroke) {
+        self.path(vec![from, to], false, stroke, None);
+    }
+
+    fn circle(&mut self, center: Point, radius: f32, stroke: Stroke) {
+        self.shapes.push(Shape::Circle { center, radius, stroke });
+    }
+
+    fn text(&mut self, at: Point, text: impl Into<String>, size: f32, anchor: Anchor, angle: f32) {
+        self.shapes.push(Shape::Text { at, text: text.into(), size, anchor, angle });
+    }
+
+    fn frame(&mut self) {
+        let (x0, y0) = (FRAME_MARGIN, FRAME_MARGIN);
+        let (x1, y1) = (SHEET_WIDTH - FRAME_MARGIN, SHEET_HEIGHT - FRAME_MARGIN);
+        self.path(vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)], true, Stroke::Visible, None);
+    }
+
+    /// Filled arrowhead with its tip at `tip`, pointing along the unit vector `direction`
+    fn arrow(&mut self, tip: Point, direction: Point) {
+        let base = (tip.0 - direction.0 * ARROW_LENGTH, tip.1 - direction.1 * ARROW_LENGTH);
+        let side = (-direction.1 * ARROW_WIDTH / 2.0, direction.0 * ARROW_WIDTH / 2.0);
+        self.path(
+            vec![tip, (base.0 + side.0, base.1 + side.1), (base.0 - side.0, base.1 - side.1)],
+            true,
+            Stroke::Thin,
+            Some(0.0),
+        );
+    }
+
+    /// 45° section hatching inside a closed outline
+    fn hatch(&mut self, outline: &[Point]) {
+        // Lines of constant x + y, on a grid shared by all outlines so adjoining areas line up
+        let step = HATCH_SPACING * SQRT_2;
+        let (low, high) = outline
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(low, high), p| (low.min(p.0 + p.1), high.max(p.0 + p.1)));
+        let mut c = (low / step).floor() * step + step;
+        while c < high {
+            let mut crossings: Vec<f32> = outline
+                .iter()
+                .zip(outline.iter().cycle().skip(1))
+                .filter_map(|(p, q)| {
+                    let (cp, cq) = (p.0 + p.1, q.0 + q.1);
+                    ((cp <= c) != (cq <= c)).then(|| p.0 + (c - cp) / (cq - cp) * (q.0 - p.0))
+                })
+                .collect();
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            for pair in crossings.chunks_exact(2) {
+                self.line((pair[0], c - pair[0]), (pair[1], c - pair[1]), Stroke::Thin);
+            }
+            c += step;
+        }
+    }
+
+    /// Horizontal distance between two features, dimensioned on a line at height `y`
+    fn horizontal_dimension(&mut self, a: Point, b: Point, y: f32, label: &str) {
+        for p in [a, b] {
+            let direction = (y - p.1).signum();
+            self.line((p.0, p.1 + direction * EXTENSION_GAP), (p.0, y + direction * EXTENSION_OVERSHOOT), Stroke::Thin);
+        }
+        let (left, right) = (a.0.min(b.0), a.0.max(b.0));
+        let width = text_width(label, TEXT_SIZE);
+
+        if right - left > 2.0 * ARROW_LENGTH + 1.0 {
+            self.line((left, y), (right, y), Stroke::Thin);
+            self.arrow((left, y), (-1.0, 0.0));
+            self.arrow((right, y), (1.0, 0.0));
+        } else {
+            // Too narrow for the arrows: point them in from outside
+            self.line((left - ARROW_LENGTH - 2.0, y), (right + ARROW_LENGTH + 2.0, y), Stroke::Thin);
+            self.arrow((left, y), (1.0, 0.0));
+            self.arrow((right, y), (-1.0, 0.0));
+        }
+        if right - left > width + 2.0 * ARROW_LENGTH {
+            self.text(((left + right) / 2.0, y - 1.0), label, TEXT_SIZE, Anchor::Middle, 0.0);
+        } else {
+            self.text((right + ARROW_LENGTH + 3.0, y - 1.0), label, TEXT_SIZE, Anchor::Start, 0.0);
+        }
+    }
+
+    /// Vertical distance between two features, dimensioned on a line at `x`
+    fn vertical_dimension(&mut self, a: Point, b: Point, x: f32, label: &str) {
+        for p in [a, b] {
+            let direction = (x - p.0).signum();
+            self.line((p.0 + direction * EXTENSION_GAP, p.1), (x + direction * EXTENSION_OVERSHOOT, p.1), Stroke::Thin);
+        }
+        let (upper, lower) = (a.1.min(b.1), a.1.max(b.1));
+        let width = text_width(label, TEXT_SIZE);
+
+        if lower - upper > 2.0 * ARROW_LENGTH + 1.0 {
+            self.line((x, upper), (x, lower), Stroke::Thin);
+            self.arrow((x, upper), (0.0, -1.0));
+            self.arrow((x, lower), (0.0, 1.0));
+        } else {
+            self.line((x, upper - ARROW_LENGTH - 2.0), (x, lower + ARROW_LENGTH + 2.0), Stroke::Thin);
+            self.arrow((x, upper), (0.0, 1.0));
+            self.arrow((x, lower), (0.0, -1.0));
+        }
+        // Vertical text reads from the bottom up, to the left of the line
+        if lower - upper > width + 2.0 * ARROW_LENGTH {
+            self.text((x - 1.0, (upper + lower) / 2.0), label, TEXT_SIZE, Anchor::Middle, -90.0);
+        } else {
+            self.text((x - 1.0, upper - ARROW_LENGTH - 3.0), label, TEXT_SIZE, Anchor::Start, -90.0);
+        }
+    }
+
+    /// Note with a line from `elbow` to an arrow at `target`, and a short
+    /// horizontal shoulder carrying the text away from the target
+    fn leader(&mut self, target: Point, elbow: Point, label: &str) {
+        let (dx, dy) = (target.0 - elbow.0, target.1 - elbow.1);
+        let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        let side = if elbow.0 >= target.0 { 1.0 } else { -1.0 };
+        let shoulder = (elbow.0 + side * LEADER_SHOULDER, elbow.1);
+
+        self.path(vec![target, elbow, shoulder], false, Stroke::Thin, None);
+        self.arrow(target, (dx / length, dy / length));
+        let anchor = if side > 0.0 { Anchor::Start } else { Anchor::End };
+        self.text((shoulder.0 + side, elbow.1 + TEXT_SIZE * 0.35), label, TEXT_SIZE, anchor, 0.0);
+    }
+
+    fn to_svg(&self, name: &str) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\" \
+             font-family=\"Helvetica, Arial, sans-serif\">",
+            w = SHEET_WIDTH,
+            h = SHEET_HEIGHT
+        );
+        let _ = writeln!(svg, "  <title>{} drawing</title>", xml_escape(name));
+        let _ = writeln!(svg, "  <rect width=\"{}\" height=\"{}\" fill=\"#fff\"/>", SHEET_WIDTH, SHEET_HEIGHT);
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Path { points, closed, stroke, fill } => {
+                    let mut d = String::new();
+                    for (i, (x, y)) in points.iter().enumerate() {
+                        let _ = write!(d, "{}{:.3} {:.3}", if i == 0 { "M " } else { " L " }, x, y);
+                    }
+                    if *closed {
+                        d.push_str(" Z");
+                    }
+                    let fill = fill.map_or("none".to_string(), |level| {
+                        let value = (level * 255.0).round() as u8;
+                        format!("#{:02x}{:02x}{:02x}", value, value, value)
+                    });
+                    let _ = writeln!(svg, "  <path d=\"{}\" fill=\"{}\"{}/>", d, fill, svg_stroke(*stroke));
+                }
+                Shape::Circle { center, radius, stroke } => {
+                    let _ = writeln!(
+                        svg,
+                        "  <circle cx=\"{:.3}\" cy=\"{:.3}\" r=\"{:.3}\" fill=\"none\"{}/>",
+                        center.0,
+                        center.1,
+                        radius,
+                        svg_stroke(*stroke)
+                    );
+                }
+                Shape::Text { at, text, size, anchor, angle } => {
+                    let anchor = match anchor {
+                        Anchor::Start => "start",
+                        Anchor::Middle => "middle",
+                        Anchor::End => "end",
+                    };
+                    let rotate = if *angle != 0.0 {
+                        format!(" transform=\"rotate({} {:.3} {:.3})\"", angle, at.0, at.1)
+                    } else {
+                        String::new()
+                    };
+                    let _ = writeln!(
+                        svg,
+                        "  <text x=\"{:.3}\" y=\"{:.3}\" font-size=\"{}\" text-anchor=\"{}\"{}>{}</text>",
+                        at.0,
+                        at.1,
+                        size,
+                        anchor,
+                        rotate,
+                        xml_escape(text)
+                    );
+                }
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Single-page PDF with the sheet as vector graphics and Helvetica text
+    fn to_pdf(&self, name: &str) -> Vec<u8> {
+        // Millimetres with y pointing down, like the SVG
+        let k = PDF_POINTS_PER_MM;
+        let mut content = format!("{:.6} 0 0 {:.6} 0 {:.4} cm 1 J 1 j\n", k, -k, SHEET_HEIGHT * k).into_bytes();
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Path { points, closed, stroke, fill } => {
+                    let mut ops = pdf_stroke(*stroke);
+                    for (i, (x, y)) in points.iter().enumerate() {
+                        let _ = write!(ops, "{:.3} {:.3} {} ", x, y, if i == 0 { "m" } else { "l" });
+                    }
+                    match (closed, fill) {
+                        (true, Some(level)) => {
+                            let _ = writeln!(ops, "h {:.3} g B 0 g", level);
+                        }
+                        (true, None) => ops.push_str("h S\n"),
+                        (false, _) => ops.push_str("S\n"),
+                    }
+                    content.extend_from_slice(ops.as_bytes());
+                }
+                Shape::Circle { center: (cx, cy), radius: r, stroke } => {
+                    // Four cubic Béziers, one per quadrant
+                    let c = r * 0.552_284_8;
+                    let mut ops = pdf_stroke(*stroke);
+                    let _ = write!(ops, "{:.3} {:.3} m ", cx + r, cy);
+                    for [(x1, y1), (x2, y2), (x3, y3)] in [
+                        [(cx + r, cy + c), (cx + c, cy + r), (*cx, cy + r)],
+                        [(cx - c, cy + r), (cx - r, cy + c), (cx - r, *cy)],
+                        [(cx - r, cy - c), (cx - c, cy - r), (*cx, cy - r)],
+                        [(cx + c, cy - r), (cx + r, cy - c), (cx + r, *cy)],
+                    ] {
+                        let _ = write!(ops, "{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} c ", x1, y1, x2, y2, x3, y3);
+                    }
+                    ops.push_str("h S\n");
+                    content.extend_from_slice(ops.as_bytes());
+                }
+                Shape::Text { at, text, size, anchor, angle } => {
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    let shift = match anchor {
+                        Anchor::Start => 0.0,
+                        Anchor::Middle => text_width(text, *size) / 2.0,
+                        Anchor::End => text_width(text, *size),
+                    };
+                    let (x, y) = (at.0 - shift * cos, at.1 - shift * sin);
+                    // Text space is flipped back so the glyphs stand upright
+                    let ops = format!(
+                        "BT /F1 {} Tf {:.4} {:.4} {:.4} {:.4} {:.3} {:.3} Tm (",
+                        size, cos, sin, sin, -cos, x, y
+                    );
+                    content.extend_from_slice(ops.as_bytes());
+                    content.extend_from_slice(&pdf_string(text));
+                    content.extend_from_slice(b") Tj ET\n");
+                }
+            }
+        }
+
+        let mut name_bytes = pdf_string(name);
+        name_bytes.extend_from_slice(b" drawing");
+        let objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>",
+                SHEET_WIDTH * k,
+                SHEET_HEIGHT * k
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+            [
+                format!("<< /Length {} >>\nstream\n", content.len()).as_bytes(),
+                &content,
+                b"\nendstream",
+            ]
+            .concat(),
+            [b"<< /Title (".as_slice(), &name_bytes, b") /Producer (Optics Ring Generator) >>"].concat(),
+        ];
+
+        let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = pdf.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            objects.len(),
+            xref
+        );
+        pdf.extend_from_slice(trailer.as_bytes());
+        pdf
+    }
+}
+
+fn svg_stroke(stroke: Stroke) -> String {
+    let mut attributes = format!(" stroke=\"#000\" stroke-width=\"{}\"", stroke.width());
+    if !stroke.dash().is_empty() {
+        let dash: Vec<String> = stroke.dash().iter().map(|d| d.to_string()).collect();
+        let _ = write!(attributes, " stroke-dasharray=\"{}\"", dash.join(" "));
+    }
+    attributes
+}
+
+fn pdf_stroke(stroke: Stroke) -> String {
+    let dash: Vec<String> = stroke.dash().iter().map(|d| d.to_string()).collect();
+    format!("{} w [{}] 0 d ", stroke.width(), dash.join(" "))
+}
+
+/// PDF literal string contents in WinAnsi encoding
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => bytes.extend_from_slice(&[b'\\', c as u8]),
+            ' '..='~' => bytes.push(c as u8),
+            '–' => bytes.push(0x96),
+            '°' | '×' | 'Ø' => bytes.push(c as u32 as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
+
+/// Approximate width of Helvetica text, for placing text anchored in the middle or at the end
+fn text_width(text: &str, size: f32) -> f32 {
+    let units: f32 = text
+        .chars()
+        .map(|c| match c {
+            ' ' | '.' | ',' | ':' | '/' | 'i' | 'j' | 'l' | 'I' | 't' | 'f' | '(' | ')' => 278.0,
+            'm' | 'w' | 'M' | 'W' => 833.0,
+            '°' => 400.0,
+            '×' => 584.0,
+            'Ø' | 'O' | 'Q' | 'G' => 778.0,
+            'A'..='Z' => 667.0,
+            'a'..='z' => 520.0,
+            _ => 556.0,
+        })
+        .sum();
+    units / 1000.0 * size
+}
+
+/// Length in millimetres without trailing zeros, e.g. `22`, `1.5` or `0.25`
+fn mm(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{LensSeat, LensSurface, SeatType};
+
+    fn seated_ring() -> RingParameters {
+        let mut params = RingParameters::with_defaults(RingType::Convex, 50.0, 40.0);
+        params.lens_seat = Some(LensSeat {
+            radius_of_curvature: 60.0,
+            surface: LensSurface::Convex,
+            seat_type: SeatType::Toroidal { radius: Some(0.4) },
+            contact_diameter: Some(42.0),
+        });
+        params.validate().unwrap();
+        params
+    }
+
+    /// Every element is closed in order, attribute values are quoted and text has no bare markup
+    fn assert_well_formed(svg: &str) {
+        let mut open = Vec::new();
+        let mut rest = svg;
+        while let Some(start) = rest.find('<') {
+            let text = &rest[..start];
+            assert!(!text.contains('>'), "bare '>' in {:?}", text);
+            for (i, _) in text.match_indices('&') {
+                let entity = &text[i..text[i..].find(';').map_or(text.len(), |end| i + end + 1)];
+                assert!(["&amp;", "&lt;", "&gt;", "&quot;"].contains(&entity), "bare '&' in {:?}", text);
+            }
+
+            let end = start + rest[start..].find('>').expect("unterminated tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('?') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name), "mismatched closing tag");
+                continue;
+            }
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let (name, attributes) = tag.split_once(' ').unwrap_or((tag, ""));
+            assert_eq!(attributes.matches('"').count() % 2, 0, "unbalanced quotes in <{}>", tag);
+            assert!(!attributes.contains('<'), "'<' in the attributes of <{}>", name);
+            if !self_closing {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty(), "unclosed elements {:?}", open);
+        assert!(rest.trim().is_empty());
+    }
+
+    /// Texts placed on the drawing
+    fn svg_texts(svg: &str) -> Vec<&str> {
+        svg.split("<text ")
+            .skip(1)
+            .map(|text| &text[text.find('>').unwrap() + 1..text.find("</text>").unwrap()])
+            .collect()
+    }
+
+    #[test]
+    fn svg_is_well_formed_and_dimensioned() {
+        for ring_type in RingType::ALL {
+            let params = RingParameters::new(ring_type, 50.0, 40.0).unwrap();
+            let svg = String::from_utf8(drawing_document(&params, DrawingFormat::Svg).unwrap()).unwrap();
+            assert_well_formed(&svg);
+
+            let texts = svg_texts(&svg);
+            for dimension in ["Ø50", "Ø40", "22"] {
+                assert!(texts.contains(&dimension), "{}: {} missing from {:?}", ring_type, dimension, texts);
+            }
+        }
+
+        let svg = String::from_utf8(drawing_document(&seated_ring(), DrawingFormat::Svg).unwrap()).unwrap();
+        assert_well_formed(&svg);
+        let texts = svg_texts(&svg);
+        for dimension in ["Ø50", "Ø40", "22", "Ø42", "12", "SR60 toroidal seat R0.4"] {
+            assert!(texts.contains(&dimension), "seat: {} missing from {:?}", dimension, texts);
+        }
+    }
+
+    #[test]
+    fn pdf_has_a_valid_cross_reference_table() {
+        let params = seated_ring();
+        let pdf = drawing_document(&params, DrawingFormat::Pdf).unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // startxref points at the table, and every entry at its object
+        let text = String::from_utf8_lossy(&pdf);
+        let startxref: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref\n0 "));
+        let table = String::from_utf8_lossy(&pdf[startxref..]);
+        let mut lines = table.lines().skip(1);
+        let size: usize = lines.next().unwrap().split(' ').nth(1).unwrap().parse().unwrap();
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for number in 1..size {
+            let entry = lines.next().unwrap();
+            assert_eq!(entry.len(), 19, "entry {:?}", entry);
+            assert!(entry.ends_with(" 00000 n "));
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", number).as_bytes()), "object {}", number);
+        }
+        assert_eq!(lines.next(), Some("trailer"));
+        assert!(lines.next().unwrap().starts_with(&format!("<< /Size {} /Root 1 0 R", size)));
+
+        // Content is uncompressed, so the dimension texts can be found as they are drawn
+        for dimension in ["Ø50", "Ø40", "22", "Ø42", "SR60 toroidal seat R0.4"] {
+            let drawn = [b"(".as_slice(), &pdf_string(dimension), b") Tj"].concat();
+            assert!(pdf.windows(drawn.len()).any(|w| w == drawn), "{} missing", dimension);
+        }
+    }
+}
@@ -1,13 +1,17 @@
 use anyhow::Result;
-use nalgebra::{Point3, Vector3};
-use stl_io::Triangle;
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::Mesh;
 
 /// Represents the three types of support rings
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum RingType {
+    #[serde(rename = "CX", alias = "convex")]
     Convex,   // CX
+    #[serde(rename = "CC", alias = "concave")]
     Concave,  // CC
-    ThreePoint, // 3P
+    #[serde(rename = "3P", alias = "three-point")]
+    ThreePoint, // 3P, or NP with `contact_count` pads
 }
 
 impl std::fmt::Display for RingType {
@@ -20,6 +24,18 @@ impl std::fmt::Display for RingType {
     }
 }
 
+impl RingType {
+    pub const ALL: [RingType; 3] = [RingType::Convex, RingType::Concave, RingType::ThreePoint];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RingType::Convex => "Convex ring with stepped recesses, the standard support",
+            RingType::Concave => "Concave ring with a dished top, a curved support",
+            RingType::ThreePoint => "Point-contact ring with 3 or more pads, minimal contact",
+        }
+    }
+}
+
 impl std::str::FromStr for RingType {
     type Err = anyhow::Error;
 
@@ -33,42 +49,875 @@ impl std::str::FromStr for RingType {
     }
 }
 
-/// Parameters for generating a support ring
-#[derive(Debug, Clone)]
+/// Curvature of the lens surface that rests on the ring
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LensSurface {
+    Convex,
+    Concave,
+}
+
+impl std::fmt::Display for LensSurface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LensSurface::Convex => write!(f, "convex"),
+            LensSurface::Concave => write!(f, "concave"),
+        }
+    }
+}
+
+impl std::str::FromStr for LensSurface {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "CX" | "CONVEX" => Ok(LensSurface::Convex),
+            "CC" | "CONCAVE" => Ok(LensSurface::Concave),
+            _ => Err(anyhow::anyhow!("Invalid lens surface: {}. Valid surfaces are: convex, concave", s)),
+        }
+    }
+}
+
+/// Shape of the ring surface that meets the lens. Serialized as a `seat_type`
+/// name with the torus radius beside it, in the lens seat's own fields.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "seat_type", rename_all = "kebab-case")]
+pub enum SeatType {
+    /// Spherical zone matching the lens radius of curvature
+    Spherical,
+    /// Cone tangent to the lens surface at the contact diameter
+    Tangent,
+    /// Square edge touching the lens at the contact diameter
+    SharpCorner,
+    /// Rounded edge with the given torus (section) radius, tangent to the lens.
+    /// Without a radius it follows the seat width, see `DEFAULT_TORUS_RADIUS_RATIO`.
+    Toroidal {
+        #[serde(rename = "torus_radius", default, skip_serializing_if = "Option::is_none")]
+        radius: Option<f32>,
+    },
+}
+
+/// Torus radius used when a toroidal seat is requested without one, as a
+/// fraction of the seat width, so a centred contact always leaves room for it
+pub const DEFAULT_TORUS_RADIUS_RATIO: f32 = 0.25;
+
+/// Gap left under the lens next to sharp-corner and toroidal contacts (mm)
+const SEAT_RELIEF: f32 = 0.2;
+
+impl std::fmt::Display for SeatType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeatType::Spherical => write!(f, "spherical"),
+            SeatType::Tangent => write!(f, "tangent"),
+            SeatType::SharpCorner => write!(f, "sharp-corner"),
+            SeatType::Toroidal { .. } => write!(f, "toroidal"),
+        }
+    }
+}
+
+impl std::str::FromStr for SeatType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "SPHERICAL" | "SPHERE" => Ok(SeatType::Spherical),
+            "TANGENT" | "CONICAL" | "CONE" => Ok(SeatType::Tangent),
+            "SHARP" | "SHARP-CORNER" | "SHARPCORNER" | "CORNER" => Ok(SeatType::SharpCorner),
+            "TOROIDAL" | "TORUS" => Ok(SeatType::Toroidal { radius: None }),
+            _ => Err(anyhow::anyhow!(
+                "Invalid seat type: {}. Valid types are: spherical, tangent, sharp-corner, toroidal",
+                s
+            )),
+        }
+    }
+}
+
+/// Shape of the top of a point-contact ring pad
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PadTip {
+    /// Flat top across the whole pad
+    #[serde(rename = "flat")]
+    Flat,
+    /// Half-cylinder running radially across the wall, giving a line contact
+    #[serde(rename = "ridge")]
+    CylindricalRidge,
+    /// Spherical cap centered on the wall, giving a point contact
+    #[serde(rename = "ball")]
+    SphericalBall,
+}
+
+impl std::fmt::Display for PadTip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PadTip::Flat => write!(f, "flat"),
+            PadTip::CylindricalRidge => write!(f, "ridge"),
+            PadTip::SphericalBall => write!(f, "ball"),
+        }
+    }
+}
+
+impl std::str::FromStr for PadTip {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "FLAT" => Ok(PadTip::Flat),
+            "RIDGE" | "CYLINDRICAL" | "CYLINDRICAL-RIDGE" | "CYLINDER" => Ok(PadTip::CylindricalRidge),
+            "BALL" | "SPHERICAL" | "SPHERICAL-BALL" | "SPHERE" => Ok(PadTip::SphericalBall),
+            _ => Err(anyhow::anyhow!("Invalid pad tip: {}. Valid tips are: flat, ridge, ball", s)),
+        }
+    }
+}
+
+/// Lens seat of a CX or CC ring, matched to the lens surface it carries
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LensSeat {
+    /// Radius of curvature of the lens surface (mm)
+    pub radius_of_curvature: f32,
+    pub surface: LensSurface,
+    #[serde(flatten)]
+    pub seat_type: SeatType,
+    /// Diameter at which the lens touches the seat; the middle of the seat when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_diameter: Option<f32>,
+}
+
+impl LensSeat {
+    /// +1 when the lens surface sags towards the ring at its center, -1 otherwise
+    fn sign(&self) -> f32 {
+        match self.surface {
+            LensSurface::Convex => 1.0,
+            LensSurface::Concave => -1.0,
+        }
+    }
+}
+
+/// Sag of a spherical surface with radius of curvature `radius` at radial distance `r`
+pub fn sag(radius: f32, r: f32) -> f32 {
+    radius - (radius * radius - r * r).sqrt()
+}
+
+/// Slope dz/dr of a spherical surface at radial distance `r`
+fn surface_slope(radius: f32, r: f32) -> f32 {
+    r / (radius * radius - r * r).sqrt()
+}
+
+/// Where a lens seat sits in the ring profile
+#[derive(Debug, Clone, Copy)]
+pub struct SeatLayout {
+    pub seat: LensSeat,
+    /// Radial extent of the seat surface
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    /// Line of contact with the lens
+    pub contact_radius: f32,
+    pub contact_height: f32,
+}
+
+impl SeatLayout {
+    /// Height of the lens surface at radius `r` while it rests on the contact line
+    pub fn lens_height_at(&self, r: f32) -> f32 {
+        let radius = self.seat.radius_of_curvature;
+        self.contact_height + self.seat.sign() * (sag(radius, r) - sag(radius, self.contact_radius))
+    }
+
+    /// Section radius of a toroidal seat, derived from the seat width when not given
+    pub fn torus_radius(&self) -> Option<f32> {
+        match self.seat.seat_type {
+            SeatType::Toroidal { radius } => {
+                Some(radius.unwrap_or((self.outer_radius - self.inner_radius) * DEFAULT_TORUS_RADIUS_RATIO))
+            }
+            _ => None,
+        }
+    }
+
+    /// Start point and edges of the seat surface, running from its outer edge
+    /// in to its inner edge. The contact line is always one of the points.
+    pub fn profile(&self) -> (ProfilePoint, Vec<ProfileSegment>) {
+        let seat = &self.seat;
+        let radius = seat.radius_of_curvature;
+        let sign = seat.sign();
+        let (r_in, r_out) = (self.inner_radius, self.outer_radius);
+        let contact = (self.contact_radius, self.contact_height);
+        let line = |r: f32, z: f32| ProfileSegment::Line { end: (r, z) };
+        let arc = |r: f32, z: f32, center: ProfilePoint, ccw: bool| ProfileSegment::Arc { end: (r, z), center, ccw };
+
+        match seat.seat_type {
+            SeatType::Spherical => {
+                // Spherical zone centred on the axis, at the lens' centre of curvature
+                let center_offset = (radius * radius - contact.0.powi(2)).sqrt();
+                let center = (0.0, contact.1 + sign * center_offset);
+                let ccw = sign < 0.0;
+                (
+                    (r_out, self.lens_height_at(r_out)),
+                    vec![
+                        arc(contact.0, contact.1, center, ccw),
+                        arc(r_in, self.lens_height_at(r_in), center, ccw),
+                    ],
+                )
+            }
+            SeatType::Tangent => {
+                let slope = sign * surface_slope(radius, contact.0);
+                let cone = |r: f32| contact.1 + slope * (r - contact.0);
+                ((r_out, cone(r_out)), vec![line(contact.0, contact.1), line(r_in, cone(r_in))])
+            }
+            SeatType::SharpCorner => {
+                // Flat on the side where the lens curves away, relieved on the side where it comes closer
+                if sign > 0.0 {
+                    let relief = self.lens_height_at(r_in) - SEAT_RELIEF;
+                    (
+                        (r_out, contact.1),
+                        vec![line(contact.0, contact.1), line(contact.0, relief), line(r_in, relief)],
+                    )
+                } else {
+                    let relief = self.lens_height_at(r_out) - SEAT_RELIEF;
+                    (
+                        (r_out, relief),
+                        vec![line(contact.0, relief), line(contact.0, contact.1), line(r_in, contact.1)],
+                    )
+                }
+            }
+            SeatType::Toroidal { .. } => {
+                let torus_radius = self.torus_radius().expect("toroidal seat");
+                // Torus section centre lies on the lens normal through the contact point
+                let normal_z = (radius * radius - contact.0.powi(2)).sqrt() / radius;
+                let normal_r = sign * contact.0 / radius;
+                let center = (contact.0 + torus_radius * normal_r, contact.1 - torus_radius * normal_z);
+                let crest = (center.0, center.1 + torus_radius);
+
+                if sign > 0.0 {
+                    let side = center.0 - torus_radius;
+                    let relief = self.lens_height_at(r_in).min(center.1) - SEAT_RELIEF;
+                    (
+                        (r_out, crest.1),
+                        vec![
+                            line(crest.0, crest.1),
+                            arc(contact.0, contact.1, center, true),
+                            arc(side, center.1, center, true),
+                            line(side, relief),
+                            line(r_in, relief),
+                        ],
+                    )
+                } else {
+                    let side = center.0 + torus_radius;
+                    let relief = self.lens_height_at(r_out).min(center.1) - SEAT_RELIEF;
+                    (
+                        (r_out, relief),
+                        vec![
+                            line(side, relief),
+                            line(side, center.1),
+                            arc(contact.0, contact.1, center, true),
+                            arc(crest.0, crest.1, center, true),
+                            line(r_in, crest.1),
+                        ],
+                    )
+                }
+            }
+        }
+    }
+
+    /// All corner points of the seat profile (arcs are monotonic between them)
+    fn points(&self) -> Vec<ProfilePoint> {
+        let (start, segments) = self.profile();
+        std::iter::once(start).chain(segments.iter().map(|s| s.end())).collect()
+    }
+}
+
+/// Default ring height from the technical drawing (mm)
+pub const DEFAULT_HEIGHT: f32 = 22.0;
+/// Default depth of the upper lens recess of the convex ring (mm)
+pub const DEFAULT_TOP_STEP_HEIGHT: f32 = 10.0;
+/// Default depth of the lower recess of the convex ring (mm)
+pub const DEFAULT_BOTTOM_STEP_HEIGHT: f32 = 2.0;
+/// Default radial width of the inner step of the convex ring (mm)
+pub const DEFAULT_INNER_STEP_WIDTH: f32 = 2.0;
+/// Default depth of the 30° chamfers of the convex ring (mm)
+pub const DEFAULT_CHAMFER_DEPTH: f32 = 1.5;
+
+/// Default height of the point-contact pads above the base annulus (mm)
+pub const DEFAULT_PAD_HEIGHT: f32 = 2.0;
+/// Default width of the point-contact pads (mm)
+pub const DEFAULT_PAD_WIDTH: f32 = 4.0;
+/// Default number of contact pads of a point-contact ring
+pub const DEFAULT_CONTACT_COUNT: u32 = 3;
+
+/// Default maximum deviation of the tessellated surface from the true circle (mm)
+pub const DEFAULT_CHORD_TOLERANCE: f32 = 0.05;
+/// Bounds on the number of segments around the ring
+const MIN_SEGMENTS: usize = 24;
+const MAX_SEGMENTS: usize = 2048;
+
+/// Chamfer angle from the technical drawing, measured from the wall it cuts
+pub const CHAMFER_ANGLE_DEG: f32 = 30.0;
+
+/// How finely curved surfaces are split into triangles
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tessellation {
+    /// Maximum distance between a chord and the circle it approximates (mm)
+    ChordTolerance(f32),
+    /// Maximum angle covered by one segment (degrees)
+    AngularTolerance(f32),
+}
+
+impl Default for Tessellation {
+    fn default() -> Self {
+        Tessellation::ChordTolerance(DEFAULT_CHORD_TOLERANCE)
+    }
+}
+
+impl Tessellation {
+    fn validate(&self) -> Result<()> {
+        match *self {
+            Tessellation::ChordTolerance(tolerance) if tolerance <= 0.0 => {
+                Err(anyhow::anyhow!("Chord tolerance must be positive"))
+            }
+            Tessellation::AngularTolerance(degrees) if degrees <= 0.0 || degrees > 90.0 => {
+                Err(anyhow::anyhow!("Angular tolerance must be between 0° and 90°"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of segments around a full circle of the given radius
+    pub fn segments_for_radius(&self, radius: f32) -> usize {
+        let max_angle = match *self {
+            // A chord spanning angle a deviates from the circle by r * (1 - cos(a / 2))
+            Tessellation::ChordTolerance(tolerance) => {
+                2.0 * (1.0 - (tolerance / radius).min(1.0)).acos()
+            }
+            Tessellation::AngularTolerance(degrees) => degrees.to_radians(),
+        };
+        // Allow for rounding, so that e.g. 10° gives 36 segments rather than 37
+        let segments = (2.0 * std::f32::consts::PI / max_angle - 1e-3).ceil() as usize;
+        segments.clamp(MIN_SEGMENTS, MAX_SEGMENTS)
+    }
+}
+
+/// Parameters for generating a support ring. The serialized form lists every
+/// field; `validate` is not run on deserialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RingParameters {
     pub ring_type: RingType,
     pub outer_diameter: f32,
     pub inner_diameter: f32,
     pub height: f32,
+    /// Depth of the upper recess, measured down from the top face (convex ring)
+    pub top_step_height: f32,
+    /// Depth of the lower recess, measured up from the base (convex ring)
+    pub bottom_step_height: f32,
+    /// Height of the middle step between the upper recess and the bore; 0 for none (convex ring)
+    pub middle_step_height: f32,
+    /// Radial width of the lens step, from the bore to the recess wall (convex ring)
+    pub inner_step_width: f32,
+    /// Depth of the 30° chamfers on the outer edges and the recess entry; 0 for none (convex ring)
+    pub chamfer_depth: f32,
+    /// Lens seat replacing the flat step (CX) or the dished top (CC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lens_seat: Option<LensSeat>,
+    /// Number of contact pads, evenly spaced (point-contact ring)
+    pub contact_count: u32,
+    /// Angle of the first pad from the +X axis, in degrees (point-contact ring)
+    pub contact_offset_deg: f32,
+    /// Distance of the pad centers from the axis; the middle of the wall when unset (point-contact ring)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pad_radius: Option<f32>,
+    /// Width of each contact pad across the ring, at the pad radius (point-contact ring)
+    pub pad_width: f32,
+    /// Height of the contact pads above the base annulus; part of the ring height (point-contact ring)
+    pub pad_height: f32,
+    pub pad_tip: PadTip,
+    pub tessellation: Tessellation,
 }
 
 impl RingParameters {
     pub fn new(ring_type: RingType, outer_diameter: f32, inner_diameter: f32) -> Result<Self> {
-        if outer_diameter <= inner_diameter {
+        let params = Self::with_defaults(ring_type, outer_diameter, inner_diameter);
+        params.validate()?;
+
+        Ok(params)
+    }
+
+    /// Ring of the given size with every other field at its default, not yet
+    /// validated, so that overrides can be applied before `validate`
+    pub fn with_defaults(ring_type: RingType, outer_diameter: f32, inner_diameter: f32) -> Self {
+        // Thin walls get a narrower step and smaller chamfers so the defaults always fit
+        let wall = (outer_diameter - inner_diameter) / 2.0;
+        let inner_step_width = DEFAULT_INNER_STEP_WIDTH.min(wall * 0.5);
+        let chamfer_depth = DEFAULT_CHAMFER_DEPTH.min((wall - inner_step_width) * 0.25 / chamfer_run(1.0));
+        let pad_width = DEFAULT_PAD_WIDTH.min(wall * 0.8);
+
+        Self {
+            ring_type,
+            outer_diameter,
+            inner_diameter,
+            height: DEFAULT_HEIGHT,
+            top_step_height: DEFAULT_TOP_STEP_HEIGHT,
+            bottom_step_height: DEFAULT_BOTTOM_STEP_HEIGHT,
+            middle_step_height: 0.0,
+            inner_step_width,
+            chamfer_depth,
+            lens_seat: None,
+            contact_count: DEFAULT_CONTACT_COUNT,
+            contact_offset_deg: 0.0,
+            pad_radius: None,
+            pad_width,
+            pad_height: DEFAULT_PAD_HEIGHT,
+            pad_tip: PadTip::Flat,
+            tessellation: Tessellation::default(),
+        }
+    }
+
+    /// Change the ring height. The step levels scale with it so the
+    /// proportions of the technical drawing are kept. Nothing is validated,
+    /// so other fields can still be set before `validate`.
+    pub fn with_height(mut self, height: f32) -> Self {
+        let scale = height / self.height;
+        self.height = height;
+        self.top_step_height *= scale;
+        self.bottom_step_height *= scale;
+        self.middle_step_height *= scale;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.outer_diameter <= self.inner_diameter {
             return Err(anyhow::anyhow!("Outer diameter must be greater than inner diameter"));
         }
-        if outer_diameter <= 0.0 || inner_diameter <= 0.0 {
+        if self.outer_diameter <= 0.0 || self.inner_diameter <= 0.0 {
             return Err(anyhow::anyhow!("Diameters must be positive"));
         }
+        if self.height <= 0.0 {
+            return Err(anyhow::anyhow!("Height must be positive"));
+        }
+        self.tessellation.validate()?;
+        if self.ring_type == RingType::Convex {
+            self.validate_convex_steps()?;
+        }
+        if self.ring_type == RingType::ThreePoint {
+            self.validate_pads()?;
+        }
+        if self.lens_seat.is_some() {
+            self.validate_lens_seat()?;
+        }
 
-        // Fixed height of 22mm as per technical drawing specification
-        let height = 22.0;
+        Ok(())
+    }
 
-        Ok(Self {
-            ring_type,
-            outer_diameter,
-            inner_diameter,
-            height,
-        })
+    fn validate_lens_seat(&self) -> Result<()> {
+        let Some(seat) = self.lens_seat else {
+            return Ok(());
+        };
+        if self.ring_type == RingType::ThreePoint {
+            return Err(anyhow::anyhow!("Lens seats are only available for CX and CC rings"));
+        }
+        if self.middle_step_height > 0.0 {
+            return Err(anyhow::anyhow!("A lens seat replaces the middle step; set the middle step height to 0"));
+        }
+
+        let layout = self.seat_layout().expect("seat is set");
+        if seat.radius_of_curvature <= layout.outer_radius {
+            return Err(anyhow::anyhow!(
+                "Lens radius of curvature ({:.1}mm) must be larger than the seat radius ({:.1}mm)",
+                seat.radius_of_curvature, layout.outer_radius
+            ));
+        }
+        if layout.contact_radius < layout.inner_radius || layout.contact_radius > layout.outer_radius {
+            return Err(anyhow::anyhow!(
+                "Contact diameter ({:.1}mm) must lie on the seat, between {:.1}mm and {:.1}mm",
+                layout.contact_radius * 2.0, layout.inner_radius * 2.0, layout.outer_radius * 2.0
+            ));
+        }
+        if let Some(radius) = layout.torus_radius() {
+            if radius <= 0.0 || radius >= seat.radius_of_curvature {
+                return Err(anyhow::anyhow!(
+                    "Torus radius ({:.2}mm) must be positive and smaller than the lens radius",
+                    radius
+                ));
+            }
+        }
+
+        // The seat has to stay between the floor below it and the top of the ring
+        let (floor, ceiling) = match self.ring_type {
+            RingType::Convex => (self.bottom_step_height, self.height - self.chamfer_depth.min(self.top_step_height)),
+            _ => (0.0, self.height),
+        };
+        for (r, z) in layout.points() {
+            if r < layout.inner_radius - 1e-4 || r > layout.outer_radius + 1e-4 {
+                return Err(anyhow::anyhow!(
+                    "The lens seat does not fit on the {:.1}mm to {:.1}mm seat diameter range; \
+                     move the contact diameter or use a smaller torus radius",
+                    layout.inner_radius * 2.0, layout.outer_radius * 2.0
+                ));
+            }
+            if z <= floor || z > ceiling + 1e-4 {
+                return Err(anyhow::anyhow!(
+                    "The lens seat runs out of the ring at {:.1}mm diameter ({:.2}mm high, allowed {:.2}mm to {:.2}mm)",
+                    r * 2.0, z, floor, ceiling
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Radial extent, contact line and heights of the lens seat, if the ring has one
+    pub fn seat_layout(&self) -> Option<SeatLayout> {
+        let seat = self.lens_seat?;
+        let inner_radius = self.inner_diameter / 2.0;
+        let outer_radius = match self.ring_type {
+            RingType::Convex => inner_radius + self.inner_step_width,
+            _ => self.outer_diameter / 2.0,
+        };
+        let contact_radius = seat
+            .contact_diameter
+            .map_or((inner_radius + outer_radius) / 2.0, |d| d / 2.0);
+
+        let mut layout = SeatLayout {
+            seat,
+            inner_radius,
+            outer_radius,
+            contact_radius,
+            contact_height: 0.0,
+        };
+
+        // CX seats sit at the step level; CC seats reach up to the top of the ring
+        layout.contact_height = match self.ring_type {
+            RingType::Convex => self.height - self.top_step_height,
+            _ => {
+                let highest = layout.points().iter().map(|p| p.1).fold(f32::MIN, f32::max);
+                self.height - highest
+            }
+        };
+
+        Some(layout)
+    }
+
+    fn validate_convex_steps(&self) -> Result<()> {
+        let wall = (self.outer_diameter - self.inner_diameter) / 2.0;
+
+        // Only the convex profile has steps; the other types ignore these fields
+        if self.top_step_height < 0.0 || self.bottom_step_height < 0.0 || self.middle_step_height < 0.0 {
+            return Err(anyhow::anyhow!("Step heights cannot be negative"));
+        }
+        if self.top_step_height + self.middle_step_height + self.bottom_step_height >= self.height {
+            return Err(anyhow::anyhow!(
+                "Steps ({:.1}mm + {:.1}mm + {:.1}mm) do not fit inside the {:.1}mm ring height",
+                self.top_step_height, self.middle_step_height, self.bottom_step_height, self.height
+            ));
+        }
+        if self.inner_step_width <= 0.0 {
+            return Err(anyhow::anyhow!("Inner step width must be positive"));
+        }
+        if self.chamfer_depth < 0.0 {
+            return Err(anyhow::anyhow!("Chamfer depth cannot be negative"));
+        }
+        if self.middle_step_height > 0.0 && self.top_step_height <= 0.0 {
+            return Err(anyhow::anyhow!("A middle step needs an upper recess above it"));
+        }
+
+        // The top face keeps some width between the outer and recess chamfers
+        let top_face = wall - self.inner_step_width - chamfer_run(self.chamfer_depth) * 2.0;
+        if top_face <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Inner step ({:.1}mm) and chamfers ({:.1}mm) do not fit in the {:.2}mm wall",
+                self.inner_step_width, self.chamfer_depth, wall
+            ));
+        }
+        if self.chamfer_depth * 2.0 >= self.height {
+            return Err(anyhow::anyhow!(
+                "Chamfer depth ({:.1}mm) is too large for the {:.1}mm ring height",
+                self.chamfer_depth, self.height
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_pads(&self) -> Result<()> {
+        let inner_radius = self.inner_diameter / 2.0;
+        let outer_radius = self.outer_diameter / 2.0;
+        let pad_radius = self.pad_center_radius();
+
+        if self.contact_count < 3 {
+            return Err(anyhow::anyhow!("A point-contact ring needs at least 3 pads"));
+        }
+        if pad_radius <= inner_radius || pad_radius >= outer_radius {
+            return Err(anyhow::anyhow!(
+                "Pad radius ({:.1}mm) must lie on the wall, between {:.1}mm and {:.1}mm",
+                pad_radius, inner_radius, outer_radius
+            ));
+        }
+        if self.pad_width <= 0.0 {
+            return Err(anyhow::anyhow!("Pad width must be positive"));
+        }
+        if self.pad_height <= 0.0 || self.pad_height >= self.height {
+            return Err(anyhow::anyhow!(
+                "Pad height ({:.1}mm) must be positive and less than the {:.1}mm ring height",
+                self.pad_height, self.height
+            ));
+        }
+        // A ball has to sit inside the wall; flat and ridge pads run across all of it
+        let half_width = self.pad_width / 2.0;
+        if self.pad_tip == PadTip::SphericalBall
+            && (pad_radius - half_width < inner_radius - 1e-4 || pad_radius + half_width > outer_radius + 1e-4)
+        {
+            return Err(anyhow::anyhow!(
+                "Ball pad ({:.1}mm wide at {:.1}mm radius) does not fit on the {:.1}mm to {:.1}mm wall",
+                self.pad_width, pad_radius, inner_radius, outer_radius
+            ));
+        }
+        // Leave at least a tenth of the pitch as base annulus between neighbouring pads
+        let pitch = std::f32::consts::PI * 2.0 / self.contact_count as f32;
+        if pad_half_angle(self) * 2.0 >= pitch * 0.9 {
+            return Err(anyhow::anyhow!(
+                "{} pads ({:.1}mm wide) do not fit around the {:.1}mm inner diameter",
+                self.contact_count, self.pad_width, self.inner_diameter
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Number of segments around the ring, set by the outer diameter so the
+    /// largest circle meets the tessellation tolerance. Every surface of the
+    /// ring uses this count so the seams line up.
+    pub fn segment_count(&self) -> usize {
+        self.tessellation.segments_for_radius(self.outer_diameter / 2.0)
+    }
+
+    /// Distance of the contact pad centers from the axis
+    pub fn pad_center_radius(&self) -> f32 {
+        self.pad_radius
+            .unwrap_or((self.inner_diameter + self.outer_diameter) / 4.0)
+    }
+
+    /// Type code used in file names: the ring type, or NP for point-contact rings with N pads
+    pub fn type_code(&self) -> String {
+        match self.ring_type {
+            RingType::ThreePoint => format!("{}P", self.contact_count),
+            ring_type => ring_type.to_string(),
+        }
+    }
+
+    /// File name without extension, e.g. `CX-50.0`; also used as the part name
+    pub fn file_stem(&self) -> String {
+        format!("{}-{:.1}", self.type_code(), self.inner_diameter)
     }
 
-    pub fn filename(&self) -> String {
-        format!("{}-{:.1}.stl", self.ring_type, self.inner_diameter)
+    /// Output file name with the given extension, e.g. `CX-50.0.stl`
+    pub fn filename(&self, extension: &str) -> String {
+        format!("{}.{}", self.file_stem(), extension)
+    }
+
+    /// Parameters that define this ring, as name/value pairs for file metadata.
+    /// Only the fields that apply to the ring type are included.
+    pub fn metadata(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![
+            ("ring_type", self.type_code()),
+            ("outer_diameter", self.outer_diameter.to_string()),
+            ("inner_diameter", self.inner_diameter.to_string()),
+            ("height", self.height.to_string()),
+        ];
+        match self.ring_type {
+            RingType::Convex => entries.extend([
+                ("top_step_height", self.top_step_height.to_string()),
+                ("bottom_step_height", self.bottom_step_height.to_string()),
+                ("middle_step_height", self.middle_step_height.to_string()),
+                ("inner_step_width", self.inner_step_width.to_string()),
+                ("chamfer_depth", self.chamfer_depth.to_string()),
+            ]),
+            RingType::Concave => {}
+            RingType::ThreePoint => entries.extend([
+                ("contact_count", self.contact_count.to_string()),
+                ("contact_offset", self.contact_offset_deg.to_string()),
+                ("pad_radius", self.pad_center_radius().to_string()),
+                ("pad_width", self.pad_width.to_string()),
+                ("pad_height", self.pad_height.to_string()),
+                ("pad_tip", self.pad_tip.to_string()),
+            ]),
+        }
+        if let (Some(seat), Some(layout)) = (self.lens_seat, self.seat_layout()) {
+            entries.extend([
+                ("lens_radius", seat.radius_of_curvature.to_string()),
+                ("lens_surface", seat.surface.to_string()),
+                ("seat_type", seat.seat_type.to_string()),
+                ("contact_diameter", (layout.contact_radius * 2.0).to_string()),
+            ]);
+            if let Some(radius) = layout.torus_radius() {
+                entries.push(("torus_radius", radius.to_string()));
+            }
+        }
+
+        entries
     }
 }
 
-/// Generate STL triangles for a support ring
-pub fn generate_ring_mesh(params: &RingParameters) -> Result<Vec<Triangle>> {
+/// A point in the r–z half-plane of a ring cross-section, as (radius, height)
+pub type ProfilePoint = (f32, f32);
+
+/// One edge of a ring profile, running from the end of the previous edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileSegment {
+    Line { end: ProfilePoint },
+    /// Circular arc around `center`, counter-clockwise in the r–z plane when `ccw` is set
+    Arc { end: ProfilePoint, center: ProfilePoint, ccw: bool },
+}
+
+impl ProfileSegment {
+    pub fn end(&self) -> ProfilePoint {
+        match *self {
+            ProfileSegment::Line { end } | ProfileSegment::Arc { end, .. } => end,
+        }
+    }
+}
+
+/// Closed cross-section of a ring in the r–z plane, revolved about the Z axis
+#[derive(Debug, Clone)]
+pub struct RingProfile {
+    pub start: ProfilePoint,
+    pub segments: Vec<ProfileSegment>,
+}
+
+impl RingProfile {
+    pub fn new(r: f32, z: f32) -> Self {
+        Self {
+            start: (r, z),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Current end point of the profile
+    pub fn current(&self) -> ProfilePoint {
+        self.segments.last().map_or(self.start, |s| s.end())
+    }
+
+    pub fn line_to(&mut self, r: f32, z: f32) {
+        self.segments.push(ProfileSegment::Line { end: (r, z) });
+    }
+
+    pub fn arc_to(&mut self, r: f32, z: f32, center: ProfilePoint, ccw: bool) {
+        self.segments.push(ProfileSegment::Arc { end: (r, z), center, ccw });
+    }
+
+    /// Close the profile with a straight edge back to the start point
+    pub fn close(&mut self) {
+        if self.current() != self.start {
+            self.line_to(self.start.0, self.start.1);
+        }
+    }
+
+    /// Flatten the profile into a closed polyline (first point not repeated).
+    /// Arcs are split so no piece spans more than `max_arc_angle` radians.
+    pub fn to_polyline(&self, max_arc_angle: f32) -> Vec<ProfilePoint> {
+        let mut points = vec![self.start];
+        let mut current = self.start;
+
+        for segment in &self.segments {
+            match *segment {
+                ProfileSegment::Line { end } => points.push(end),
+                ProfileSegment::Arc { end, center, ccw } => {
+                    let radius = ((current.0 - center.0).powi(2) + (current.1 - center.1).powi(2)).sqrt();
+                    let start_angle = (current.1 - center.1).atan2(current.0 - center.0);
+                    let sweep = arc_sweep(current, end, center, ccw);
+                    let steps = ((sweep.abs() / max_arc_angle).ceil() as usize).max(1);
+
+                    for step in 1..steps {
+                        let angle = start_angle + sweep * step as f32 / steps as f32;
+                        points.push((center.0 + radius * angle.cos(), center.1 + radius * angle.sin()));
+                    }
+                    points.push(end);
+                }
+            }
+            current = segment.end();
+        }
+
+        // Drop repeated points, including the closing one
+        points.dedup_by(|a, b| (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6);
+        while points.len() > 1 && {
+            let (first, last) = (points[0], points[points.len() - 1]);
+            (first.0 - last.0).abs() < 1e-6 && (first.1 - last.1).abs() < 1e-6
+        } {
+            points.pop();
+        }
+
+        points
+    }
+
+    /// Segments paired with their start points, leaving out those shorter than `min_length`
+    pub fn edges(&self, min_length: f32) -> Vec<(ProfilePoint, ProfileSegment)> {
+        let mut edges = Vec::new();
+        let mut current = self.start;
+        for segment in &self.segments {
+            let end = segment.end();
+            if ((end.0 - current.0).powi(2) + (end.1 - current.1).powi(2)).sqrt() > min_length {
+                edges.push((current, *segment));
+            }
+            current = end;
+        }
+        edges
+    }
+
+    /// Whether the profile runs counter-clockwise in the r–z plane, which
+    /// puts the material on the left of each segment
+    pub fn is_counter_clockwise(&self) -> bool {
+        polygon_signed_area(&self.to_polyline(std::f32::consts::PI / 8.0)) > 0.0
+    }
+
+    /// The same profile walked in the opposite direction
+    pub fn reversed(&self) -> RingProfile {
+        let mut points = vec![self.start];
+        points.extend(self.segments.iter().map(|s| s.end()));
+
+        let mut reversed = RingProfile::new(self.current().0, self.current().1);
+        for (i, segment) in self.segments.iter().enumerate().rev() {
+            let (r, z) = points[i];
+            match *segment {
+                ProfileSegment::Line { .. } => reversed.line_to(r, z),
+                ProfileSegment::Arc { center, ccw, .. } => reversed.arc_to(r, z, center, !ccw),
+            }
+        }
+
+        reversed
+    }
+}
+
+/// Signed sweep angle of an arc from `start` to `end` around `center`
+pub fn arc_sweep(start: ProfilePoint, end: ProfilePoint, center: ProfilePoint, ccw: bool) -> f32 {
+    let two_pi = 2.0 * std::f32::consts::PI;
+    let a1 = (start.1 - center.1).atan2(start.0 - center.0);
+    let a2 = (end.1 - center.1).atan2(end.0 - center.0);
+    let mut sweep = a2 - a1;
+
+    if ccw {
+        while sweep <= 0.0 {
+            sweep += two_pi;
+        }
+    } else {
+        while sweep >= 0.0 {
+            sweep -= two_pi;
+        }
+    }
+
+    sweep
+}
+
+/// Cross-section of a ring that is a solid of revolution (CX and CC).
+/// Point-contact rings vary around the axis and have no single profile.
+pub fn ring_profile(params: &RingParameters) -> Result<RingProfile> {
+    match params.ring_type {
+        RingType::Convex => Ok(create_convex_profile(params)),
+        RingType::Concave => Ok(create_concave_profile(params)),
+        RingType::ThreePoint => Err(anyhow::anyhow!(
+            "{} rings are not solids of revolution and have no single cross-section",
+            params.type_code()
+        )),
+    }
+}
+
+/// Generate the indexed mesh for a support ring
+pub fn generate_ring_mesh(params: &RingParameters) -> Result<Mesh> {
     match params.ring_type {
         RingType::Convex => generate_convex_ring(params),
         RingType::Concave => generate_concave_ring(params),
@@ -76,307 +925,470 @@ pub fn generate_ring_mesh(params: &RingParameters) -> Result<Vec<Triangle>> {
     }
 }
 
-fn generate_convex_ring(params: &RingParameters) -> Result<Vec<Triangle>> {
-    let mut triangles = Vec::new();
-    let segments = 64; // Number of segments for smooth curves
+fn generate_convex_ring(params: &RingParameters) -> Result<Mesh> {
+    let segments = params.segment_count();
     
-    let outer_radius = params.outer_diameter / 2.0;
-    let inner_radius = params.inner_diameter / 2.0;
-    
-    // Static dimensions from technical drawing (in mm)
-    let total_height = 22.0;
-    let top_step_height = 10.0; // Height of top step
-    let bottom_step_height = 10.0; // Height of bottom step  
-    let middle_step_height = 2.0; // Small middle step
+    // Generate the precise convex profile based on technical drawing
+    let profile = create_convex_profile(params);
     
-    // Edge dimensions
-    let chamfer_depth = 1.5; // 30° chamfer depth
-    let inner_step_width = 2.0; // Width of inner step
-    
-    // Generate the complex profile geometry
-    for i in 0..segments {
-        let angle1 = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-        let angle2 = 2.0 * std::f32::consts::PI * ((i + 1) % segments) as f32 / segments as f32;
-        
-        // Generate the precise convex profile based on technical drawing
-        add_convex_profile_segment(&mut triangles, angle1, angle2, inner_radius, outer_radius, 
-                                 total_height, top_step_height, bottom_step_height, 
-                                 middle_step_height, chamfer_depth, inner_step_width);
-    }
+    Ok(revolve_profile(&profile, segments))
+}
+
+fn generate_concave_ring(params: &RingParameters) -> Result<Mesh> {
+    let segments = params.segment_count();
     
-    // Add horizontal surfaces (top and bottom faces)
-    add_convex_horizontal_surfaces(&mut triangles, inner_radius, outer_radius, 
-                                 total_height, top_step_height, bottom_step_height,
-                                 middle_step_height, chamfer_depth, inner_step_width, segments);
+    let profile = create_concave_profile(params);
     
-    Ok(triangles)
+    Ok(revolve_profile(&profile, segments))
 }
 
-fn generate_concave_ring(params: &RingParameters) -> Result<Vec<Triangle>> {
-    let mut triangles = Vec::new();
-    let segments = 64;
+fn generate_three_point_ring(params: &RingParameters) -> Result<Mesh> {
+    let segments = params.segment_count();
+    let two_pi = 2.0 * std::f32::consts::PI;
     
     let outer_radius = params.outer_diameter / 2.0;
     let inner_radius = params.inner_diameter / 2.0;
-    let height = params.height;
-    
-    // Generate concave surface (curved outward)
-    let curve_depth = (outer_radius - inner_radius) * 0.3;
+    let pitch_radius = params.pad_center_radius();
     
-    for i in 0..segments {
-        let angle1 = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-        let angle2 = 2.0 * std::f32::consts::PI * ((i + 1) % segments) as f32 / segments as f32;
-        
-        add_concave_segment(&mut triangles, angle1, angle2, inner_radius, outer_radius, height, curve_depth);
-    }
-    
-    add_ring_base(&mut triangles, inner_radius, outer_radius, 0.0, segments);
-    add_ring_top(&mut triangles, inner_radius, outer_radius, height, segments);
+    // Closed annulus up to the base of the pads
+    let base_height = params.height - params.pad_height;
+    let pad_radius = params.pad_width / 2.0;
+    // Ridge axis and ball center sit one pad radius below the tip
+    let tip_center = params.height - pad_radius;
     
-    Ok(triangles)
-}
-
-fn generate_three_point_ring(params: &RingParameters) -> Result<Vec<Triangle>> {
-    let mut triangles = Vec::new();
-    let segments = 64;
+    // Contact points evenly spaced from the offset angle
+    let offset = params.contact_offset_deg.to_radians();
+    let contact_angles: Vec<f32> = (0..params.contact_count)
+        .map(|i| offset + two_pi * i as f32 / params.contact_count as f32)
+        .collect();
+    let half_angle = pad_half_angle(params);
     
-    let outer_radius = params.outer_diameter / 2.0;
-    let inner_radius = params.inner_diameter / 2.0;
-    let height = params.height;
+    // Flat pads rise over a short flank (0.1mm at the inner wall) instead of a zero-width step
+    let flank = 0.1 / inner_radius;
     
-    // Generate three contact points at 120-degree intervals
-    let contact_angles = [0.0, 2.0 * std::f32::consts::PI / 3.0, 4.0 * std::f32::consts::PI / 3.0];
-    let contact_width = std::f32::consts::PI / 12.0; // 15 degrees each
+    // Regular stations plus fine ones across each pad so curved tips are resolved
+    let fine_steps = 16;
+    let mut angles: Vec<f32> = (0..segments).map(|i| two_pi * i as f32 / segments as f32).collect();
+    for &contact_angle in &contact_angles {
+        for i in 0..=fine_steps {
+            let offset = half_angle * (2.0 * i as f32 / fine_steps as f32 - 1.0);
+            angles.push((contact_angle + offset).rem_euclid(two_pi));
+        }
+        for offset in [-half_angle - flank, half_angle + flank] {
+            angles.push((contact_angle + offset).rem_euclid(two_pi));
+        }
+    }
+    angles.sort_by(|a, b| a.total_cmp(b));
+    angles.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
     
-    for i in 0..segments {
-        let angle1 = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-        let angle2 = 2.0 * std::f32::consts::PI * ((i + 1) % segments) as f32 / segments as f32;
-        
-        // Check if this segment is near a contact point
-        let is_contact_segment = contact_angles.iter().any(|&contact_angle| {
-            let diff = (angle1 - contact_angle).abs().min((angle1 - contact_angle + 2.0 * std::f32::consts::PI).abs());
-            diff < contact_width
-        });
-        
-        if is_contact_segment {
-            add_contact_segment(&mut triangles, angle1, angle2, inner_radius, outer_radius, height);
-        } else {
-            // Add minimal support structure between contact points
-            add_minimal_segment(&mut triangles, angle1, angle2, inner_radius, outer_radius, height);
+    // Radial samples across the top, from the outer wall inwards
+    let mut radii = vec![outer_radius, inner_radius];
+    match params.pad_tip {
+        PadTip::Flat => {}
+        PadTip::CylindricalRidge => {
+            radii.extend((1..8).map(|i| inner_radius + (outer_radius - inner_radius) * i as f32 / 8.0));
+        }
+        PadTip::SphericalBall => {
+            radii.extend((0..=fine_steps).map(|i| {
+                pitch_radius - pad_radius + params.pad_width * i as f32 / fine_steps as f32
+            }));
         }
     }
+    radii.sort_by(|a, b| b.total_cmp(a));
+    radii.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
     
-    add_ring_base(&mut triangles, inner_radius, outer_radius, 0.0, segments);
-    add_ring_top(&mut triangles, inner_radius, outer_radius, height, segments);
+    let top_height = |r: f32, diff: f32| -> f32 {
+        match params.pad_tip {
+            PadTip::Flat => {
+                let ramp = ((half_angle + flank - diff.abs()) / flank).clamp(0.0, 1.0);
+                base_height + params.pad_height * ramp
+            }
+            PadTip::CylindricalRidge => {
+                // Distance from the radial line through the pad center
+                let offset = r * diff.sin();
+                let cover = pad_radius * pad_radius - offset * offset;
+                if diff.cos() <= 0.0 || cover <= 0.0 {
+                    base_height
+                } else {
+                    (tip_center + cover.sqrt()).max(base_height)
+                }
+            }
+            PadTip::SphericalBall => {
+                let dx = r * diff.cos() - pitch_radius;
+                let dy = r * diff.sin();
+                let cover = pad_radius * pad_radius - dx * dx - dy * dy;
+                if cover <= 0.0 {
+                    base_height
+                } else {
+                    (tip_center + cover.sqrt()).max(base_height)
+                }
+            }
+        }
+    };
     
-    Ok(triangles)
+    let stations: Vec<(f32, Vec<ProfilePoint>)> = angles
+        .into_iter()
+        .map(|angle| {
+            // Signed angular distance to the nearest contact point
+            let diff = contact_angles
+                .iter()
+                .map(|&contact_angle| (angle - contact_angle + std::f32::consts::PI).rem_euclid(two_pi) - std::f32::consts::PI)
+                .fold(f32::MAX, |a, b| if b.abs() < a.abs() { b } else { a });
+            
+            let mut profile = vec![(inner_radius, 0.0), (outer_radius, 0.0)];
+            profile.extend(radii.iter().map(|&r| (r, top_height(r, diff))));
+            (angle, profile)
+        })
+        .collect();
+    
+    Ok(sweep_profiles(&stations))
 }
 
-// New precise convex ring geometry functions based on technical drawing
-
-fn add_convex_profile_segment(triangles: &mut Vec<Triangle>, angle1: f32, angle2: f32,
-                            inner_radius: f32, outer_radius: f32, total_height: f32,
-                            top_step_height: f32, bottom_step_height: f32, 
-                            middle_step_height: f32, chamfer_depth: f32, inner_step_width: f32) {
-    
-    // Define the precise profile points based on the technical drawing
-    let profile_points = create_convex_profile_points(inner_radius, outer_radius, total_height,
-                                                    top_step_height, bottom_step_height,
-                                                    middle_step_height, chamfer_depth, inner_step_width);
-    
-    // Create vertical surfaces by connecting profile points between angles
-    for i in 0..(profile_points.len() - 1) {
-        let (r1, z1) = profile_points[i];
-        let (r2, z2) = profile_points[i + 1];
-        
-        let p1 = Point3::new(r1 * angle1.cos(), r1 * angle1.sin(), z1);
-        let p2 = Point3::new(r2 * angle1.cos(), r2 * angle1.sin(), z2);
-        let p3 = Point3::new(r1 * angle2.cos(), r1 * angle2.sin(), z1);
-        let p4 = Point3::new(r2 * angle2.cos(), r2 * angle2.sin(), z2);
-        
-        add_quad_triangles(triangles, p1, p2, p3, p4);
+/// Angle either side of a contact point covered by a pad
+fn pad_half_angle(params: &RingParameters) -> f32 {
+    let inner_radius = params.inner_diameter / 2.0;
+    let pitch_radius = params.pad_center_radius();
+    let pad_radius = params.pad_width / 2.0;
+    match params.pad_tip {
+        PadTip::Flat => pad_radius / pitch_radius,
+        // A ridge is widest in angle at the bore
+        PadTip::CylindricalRidge => (pad_radius / inner_radius).min(1.0).asin(),
+        PadTip::SphericalBall => (pad_radius / pitch_radius).min(1.0).asin(),
     }
 }
 
-fn create_convex_profile_points(inner_radius: f32, outer_radius: f32, _total_height: f32,
-                               _top_step_height: f32, _bottom_step_height: f32,
-                               _middle_step_height: f32, _chamfer_depth: f32, _inner_step_width: f32) -> Vec<(f32, f32)> {
-    let mut points = Vec::new();
-    
-    // Create a proper convex ring profile based on technical drawing
-    // The ring has a 22mm height with internal stepped geometry for lens positioning
+/// Horizontal run of a chamfer of the given depth
+pub fn chamfer_run(depth: f32) -> f32 {
+    depth * CHAMFER_ANGLE_DEG.to_radians().tan()
+}
+
+/// Cross-section profile of the convex ring, following the technical drawing
+pub fn create_convex_profile(params: &RingParameters) -> RingProfile {
+    let outer_radius = params.outer_diameter / 2.0;
+    let inner_radius = params.inner_diameter / 2.0;
+    let total_height = params.height;
     
-    // Starting from bottom-outer, going clockwise around the profile:
+    // Internal stepped geometry for lens positioning
+    let step_radius = inner_radius + params.inner_step_width;
+    let middle_radius = inner_radius + params.inner_step_width * 0.5;
+    let top_step_level = total_height - params.top_step_height;
+    let middle_step_level = top_step_level - params.middle_step_height;
+    let bottom_step_level = params.bottom_step_height;
     
-    // 1. Bottom outer edge
-    points.push((outer_radius, 0.0));
+    // 30° chamfers on the outer edges and as a lead-in at the top of the recess
+    let chamfer = params.chamfer_depth;
+    let recess_chamfer = chamfer.min(params.top_step_height);
     
-    // 2. Straight up the outer wall
-    points.push((outer_radius, 22.0));
+    // Starting from bottom-outer, going counter-clockwise around the profile
+    let mut profile = RingProfile::new(outer_radius - chamfer_run(chamfer), 0.0);
     
-    // 3. Top surface - outer to step
-    let step_radius = inner_radius + 2.0;
-    points.push((step_radius, 22.0));
+    // Bottom chamfer and straight up the outer wall
+    profile.line_to(outer_radius, chamfer);
+    profile.line_to(outer_radius, total_height - chamfer);
     
-    // 4. Drop down to middle level (creates top recess)
-    points.push((step_radius, 12.0));
+    // Top chamfer and top surface - outer to step
+    profile.line_to(outer_radius - chamfer_run(chamfer), total_height);
+    profile.line_to(step_radius + chamfer_run(recess_chamfer), total_height);
     
-    // 5. Step in towards center
-    points.push((inner_radius, 12.0));
+    // Recess lead-in, then drop down to middle level (creates top recess)
+    profile.line_to(step_radius, total_height - recess_chamfer);
     
-    // 6. Down the inner wall to bottom step level
-    points.push((inner_radius, 2.0));
+    if let Some(layout) = params.seat_layout() {
+        // Lens seat across the step, from the recess wall in to the bore
+        add_lens_seat(&mut profile, &layout);
+    } else {
+        profile.line_to(step_radius, top_step_level);
+        
+        // Optional middle step halfway across the lens step
+        if params.middle_step_height > 0.0 {
+            profile.line_to(middle_radius, top_step_level);
+            profile.line_to(middle_radius, middle_step_level);
+        }
+        
+        // Step in towards center
+        profile.line_to(inner_radius, middle_step_level);
+    }
     
-    // 7. Step out to create bottom recess
-    points.push((step_radius, 2.0));
+    // Down the inner wall to bottom step level
+    profile.line_to(inner_radius, bottom_step_level);
     
-    // 8. Down to bottom
-    points.push((step_radius, 0.0));
+    // Step out to create bottom recess
+    profile.line_to(step_radius, bottom_step_level);
     
-    // Close the profile by returning to start point
-    points.push((outer_radius, 0.0));
+    // Down to bottom, then back along the base to the start
+    profile.line_to(step_radius, 0.0);
+    profile.close();
     
-    points
+    profile
 }
 
-fn add_convex_horizontal_surfaces(triangles: &mut Vec<Triangle>, inner_radius: f32, outer_radius: f32,
-                                _total_height: f32, _top_step_height: f32, _bottom_step_height: f32,
-                                _middle_step_height: f32, _chamfer_depth: f32, _inner_step_width: f32, segments: usize) {
-    
-    let step_radius = inner_radius + 2.0;
+/// Cross-section profile of the concave ring: flat base, straight walls and a
+/// dished top, or the lens seat when one is given
+pub fn create_concave_profile(params: &RingParameters) -> RingProfile {
+    let outer_radius = params.outer_diameter / 2.0;
+    let inner_radius = params.inner_diameter / 2.0;
+    let height = params.height;
     
-    // Bottom face - outer ring (step_radius to outer_radius)
-    add_annular_surface(triangles, step_radius, outer_radius, 0.0, segments, false);
+    let mut profile = RingProfile::new(inner_radius, 0.0);
+    profile.line_to(outer_radius, 0.0);
     
-    // Bottom step surface at 2mm height
-    add_annular_surface(triangles, inner_radius, step_radius, 2.0, segments, true);
+    if let Some(layout) = params.seat_layout() {
+        // Lens seat across the whole top, running from the outer wall back to the inner wall
+        add_lens_seat(&mut profile, &layout);
+    } else {
+        // Curve depth follows the wall thickness but never cuts through the ring
+        let half_width = (outer_radius - inner_radius) / 2.0;
+        let curve_depth = (half_width * 0.6).min(height * 0.5);
+        
+        // Circular dish through both top edges and the lowest point at mid-wall
+        let curve_radius = (half_width.powi(2) + curve_depth.powi(2)) / (2.0 * curve_depth);
+        let center = (inner_radius + half_width, height - curve_depth + curve_radius);
+        
+        profile.line_to(outer_radius, height);
+        
+        // Concave top surface, running from the outer wall back to the inner wall
+        profile.arc_to(inner_radius, height, center, false);
+    }
     
-    // Top step surface at 12mm height  
-    add_annular_surface(triangles, inner_radius, step_radius, 12.0, segments, false);
+    profile.close();
     
-    // Top face at 22mm height
-    add_annular_surface(triangles, step_radius, outer_radius, 22.0, segments, true);
+    profile
 }
 
-fn add_annular_surface(triangles: &mut Vec<Triangle>, inner_r: f32, outer_r: f32, z: f32, segments: usize, face_up: bool) {
-    for i in 0..segments {
-        let angle1 = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-        let angle2 = 2.0 * std::f32::consts::PI * ((i + 1) % segments) as f32 / segments as f32;
-        
-        let p1 = Point3::new(inner_r * angle1.cos(), inner_r * angle1.sin(), z);
-        let p2 = Point3::new(outer_r * angle1.cos(), outer_r * angle1.sin(), z);
-        let p3 = Point3::new(inner_r * angle2.cos(), inner_r * angle2.sin(), z);
-        let p4 = Point3::new(outer_r * angle2.cos(), outer_r * angle2.sin(), z);
-        
-        if face_up {
-            add_triangle(triangles, p1, p2, p3);
-            add_triangle(triangles, p2, p4, p3);
-        } else {
-            add_triangle(triangles, p1, p3, p2);
-            add_triangle(triangles, p2, p3, p4);
-        }
-    }
+/// Add a lens seat to a profile, from the outer edge of the seat to its inner edge
+fn add_lens_seat(profile: &mut RingProfile, layout: &SeatLayout) {
+    let (start, segments) = layout.profile();
+    profile.line_to(start.0, start.1);
+    profile.segments.extend(segments);
 }
 
-fn add_concave_segment(triangles: &mut Vec<Triangle>, angle1: f32, angle2: f32,
-                      inner_radius: f32, outer_radius: f32, _height: f32, curve_depth: f32) {
-    // Create curved surface that curves outward (concave from lens perspective)
-    let steps = 8;
-    for step in 0..steps {
-        let t1 = step as f32 / steps as f32;
-        let t2 = (step + 1) as f32 / steps as f32;
-        
-        let r1 = inner_radius + (outer_radius - inner_radius) * t1;
-        let r2 = inner_radius + (outer_radius - inner_radius) * t2;
-        
-        // Curve calculation - concave shape (inverted)
-        let z_offset1 = -curve_depth * (1.0 - (2.0 * t1 - 1.0).powi(2));
-        let z_offset2 = -curve_depth * (1.0 - (2.0 * t2 - 1.0).powi(2));
-        
-        let p1 = Point3::new(r1 * angle1.cos(), r1 * angle1.sin(), z_offset1);
-        let p2 = Point3::new(r2 * angle1.cos(), r2 * angle1.sin(), z_offset2);
-        let p3 = Point3::new(r1 * angle2.cos(), r1 * angle2.sin(), z_offset1);
-        let p4 = Point3::new(r2 * angle2.cos(), r2 * angle2.sin(), z_offset2);
-        
-        add_quad_triangles(triangles, p1, p2, p3, p4);
+/// Revolve a closed profile about the Z axis into a closed solid
+pub fn revolve_profile(profile: &RingProfile, segments: usize) -> Mesh {
+    let mut points = profile.to_polyline(2.0 * std::f32::consts::PI / segments as f32);
+
+    // Walk the profile counter-clockwise in the r–z plane so all normals point outward
+    if polygon_signed_area(&points) < 0.0 {
+        points.reverse();
     }
-}
 
-fn add_contact_segment(triangles: &mut Vec<Triangle>, angle1: f32, angle2: f32,
-                      inner_radius: f32, outer_radius: f32, _height: f32) {
-    // Full contact surface for three-point support
-    let p1 = Point3::new(inner_radius * angle1.cos(), inner_radius * angle1.sin(), 0.0);
-    let p2 = Point3::new(outer_radius * angle1.cos(), outer_radius * angle1.sin(), 0.0);
-    let p3 = Point3::new(inner_radius * angle2.cos(), inner_radius * angle2.sin(), 0.0);
-    let p4 = Point3::new(outer_radius * angle2.cos(), outer_radius * angle2.sin(), 0.0);
-    
-    add_quad_triangles(triangles, p1, p2, p3, p4);
-}
+    let stations: Vec<(f32, Vec<ProfilePoint>)> = (0..segments)
+        .map(|i| (2.0 * std::f32::consts::PI * i as f32 / segments as f32, points.clone()))
+        .collect();
 
-fn add_minimal_segment(triangles: &mut Vec<Triangle>, angle1: f32, angle2: f32,
-                      inner_radius: f32, outer_radius: f32, height: f32) {
-    // Minimal structure between contact points - just outer ring connection
-    let reduced_radius = inner_radius + (outer_radius - inner_radius) * 0.8;
-    
-    let p1 = Point3::new(reduced_radius * angle1.cos(), reduced_radius * angle1.sin(), height * 0.5);
-    let p2 = Point3::new(outer_radius * angle1.cos(), outer_radius * angle1.sin(), 0.0);
-    let p3 = Point3::new(reduced_radius * angle2.cos(), reduced_radius * angle2.sin(), height * 0.5);
-    let p4 = Point3::new(outer_radius * angle2.cos(), outer_radius * angle2.sin(), 0.0);
-    
-    add_quad_triangles(triangles, p1, p2, p3, p4);
+    sweep_profiles(&stations)
 }
 
-fn add_ring_base(triangles: &mut Vec<Triangle>, inner_radius: f32, outer_radius: f32, z: f32, segments: usize) {
-    for i in 0..segments {
-        let angle1 = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-        let angle2 = 2.0 * std::f32::consts::PI * ((i + 1) % segments) as f32 / segments as f32;
-        
-        let p1 = Point3::new(inner_radius * angle1.cos(), inner_radius * angle1.sin(), z);
-        let p2 = Point3::new(outer_radius * angle1.cos(), outer_radius * angle1.sin(), z);
-        let p3 = Point3::new(inner_radius * angle2.cos(), inner_radius * angle2.sin(), z);
-        let p4 = Point3::new(outer_radius * angle2.cos(), outer_radius * angle2.sin(), z);
-        
-        // Add triangles for ring base (facing down)
-        add_triangle(triangles, p1, p3, p2);
-        add_triangle(triangles, p2, p3, p4);
+/// Sweep a profile that may change shape with angle once around the Z axis.
+///
+/// Each station is an angle in `[0, 2π)` (ascending) with a counter-clockwise
+/// profile; all stations must have the same number of points so the surfaces
+/// between them line up. The last station connects back to the first.
+pub fn sweep_profiles(stations: &[(f32, Vec<ProfilePoint>)]) -> Mesh {
+    let mut mesh = Mesh::new();
+
+    // One ring of vertices per angular station, shared by neighbouring quads
+    for (angle, points) in stations {
+        for &(r, z) in points {
+            mesh.add_vertex([r * angle.cos(), r * angle.sin(), z]);
+        }
     }
-}
 
-fn add_ring_top(triangles: &mut Vec<Triangle>, inner_radius: f32, outer_radius: f32, z: f32, segments: usize) {
-    for i in 0..segments {
-        let angle1 = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-        let angle2 = 2.0 * std::f32::consts::PI * ((i + 1) % segments) as f32 / segments as f32;
-        
-        let p1 = Point3::new(inner_radius * angle1.cos(), inner_radius * angle1.sin(), z);
-        let p2 = Point3::new(outer_radius * angle1.cos(), outer_radius * angle1.sin(), z);
-        let p3 = Point3::new(inner_radius * angle2.cos(), inner_radius * angle2.sin(), z);
-        let p4 = Point3::new(outer_radius * angle2.cos(), outer_radius * angle2.sin(), z);
-        
-        // Add triangles for ring top (facing up)
-        add_triangle(triangles, p1, p2, p3);
-        add_triangle(triangles, p2, p4, p3);
+    let count = stations.first().map_or(0, |(_, points)| points.len()) as u32;
+    let station_count = stations.len() as u32;
+    for i in 0..station_count {
+        let ring1 = i * count;
+        let ring2 = ((i + 1) % station_count) * count;
+
+        for j in 0..count {
+            let next = (j + 1) % count;
+            let (p1, p2) = (ring1 + j, ring1 + next);
+            let (p3, p4) = (ring2 + j, ring2 + next);
+
+            mesh.add_triangle(p1, p3, p2);
+            mesh.add_triangle(p3, p4, p2);
+        }
     }
+
+    mesh
 }
 
-fn add_quad_triangles(triangles: &mut Vec<Triangle>, p1: Point3<f32>, p2: Point3<f32>, 
-                     p3: Point3<f32>, p4: Point3<f32>) {
-    // Split quad into two triangles
-    add_triangle(triangles, p1, p2, p3);
-    add_triangle(triangles, p2, p4, p3);
+/// Signed area of a closed polyline in the r–z plane (positive when counter-clockwise)
+fn polygon_signed_area(points: &[ProfilePoint]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let (r1, z1) = points[i];
+        let (r2, z2) = points[(i + 1) % points.len()];
+        area += r1 * z2 - r2 * z1;
+    }
+    area * 0.5
 }
 
-fn add_triangle(triangles: &mut Vec<Triangle>, p1: Point3<f32>, p2: Point3<f32>, p3: Point3<f32>) {
-    // Calculate normal
-    let edge1 = Vector3::new(p2.x - p1.x, p2.y - p1.y, p2.z - p1.z);
-    let edge2 = Vector3::new(p3.x - p1.x, p3.y - p1.y, p3.z - p1.z);
-    let normal = edge1.cross(&edge2).normalize();
-    
-    triangles.push(Triangle {
-        normal: stl_io::Vector::new([normal.x, normal.y, normal.z]),
-        vertices: [
-            stl_io::Vector::new([p1.x, p1.y, p1.z]),
-            stl_io::Vector::new([p2.x, p2.y, p2.z]),
-            stl_io::Vector::new([p3.x, p3.y, p3.z]),
-        ],
-    });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Every edge is shared by exactly two triangles that traverse it in opposite directions
+    fn assert_closed_manifold(mesh: &Mesh, label: &str) {
+        let mut edges: HashMap<(u32, u32), usize> = HashMap::new();
+        for face in &mesh.indices {
+            for k in 0..3 {
+                *edges.entry((face[k], face[(k + 1) % 3])).or_default() += 1;
+            }
+        }
+
+        for (&(a, b), &count) in &edges {
+            assert_eq!(count, 1, "{}: edge {}-{} used {} times in the same direction", label, a, b, count);
+            assert_eq!(edges.get(&(b, a)), Some(&1), "{}: edge {}-{} has no opposite half", label, a, b);
+        }
+
+        // Signed volume is positive only when the normals point outward
+        let volume: f32 = (0..mesh.triangle_count())
+            .map(|i| {
+                let [p1, p2, p3] = mesh.triangle_points(i);
+                p1.coords.dot(&p2.coords.cross(&p3.coords)) / 6.0
+            })
+            .sum();
+        assert!(volume > 0.0, "{}: signed volume {} is not positive", label, volume);
+    }
+
+    /// The ring touches the lens sphere at the requested contact diameter
+    fn assert_seat_contact(params: &RingParameters, mesh: &Mesh, requested: Option<f32>, label: &str) {
+        let layout = params.seat_layout().unwrap();
+        if let Some(diameter) = requested {
+            assert!(
+                (layout.contact_radius * 2.0 - diameter).abs() < 1e-4,
+                "{}: contact diameter {} instead of {}", label, layout.contact_radius * 2.0, diameter
+            );
+        }
+
+        // Lens sphere through the vertex height of the lens surface, independent of the seat profile
+        let radius = layout.seat.radius_of_curvature;
+        let center_z = layout.lens_height_at(0.0) + layout.seat.sign() * radius;
+        let contact = mesh
+            .vertices
+            .iter()
+            .find(|v| {
+                (v[0].hypot(v[1]) - layout.contact_radius).abs() < 1e-3 && (v[2] - layout.contact_height).abs() < 1e-3
+            })
+            .unwrap_or_else(|| panic!("{}: no vertex on the contact line", label));
+        let distance = contact[0].hypot(contact[1]).hypot(contact[2] - center_z);
+        assert!(
+            (distance - radius).abs() < 1e-2,
+            "{}: contact point {} from the lens centre, not {}", label, distance, radius
+        );
+    }
+
+    #[test]
+    fn steps_only_limit_the_height_of_convex_rings() {
+        for ring_type in [RingType::Concave, RingType::ThreePoint] {
+            let mut params = RingParameters::new(ring_type, 40.0, 30.0).unwrap();
+            params.height = 3.0;
+            assert!(params.validate().is_ok(), "{:?} ring rejected for its unused steps", ring_type);
+            generate_ring_mesh(&params).unwrap();
+        }
+
+        let mut params = RingParameters::new(RingType::Convex, 40.0, 30.0).unwrap();
+        params.height = 3.0;
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn every_ring_type_is_a_closed_solid() {
+        for ring_type in [RingType::Convex, RingType::Concave, RingType::ThreePoint] {
+            for inner_diameter in [6.0, 12.5, 25.0, 50.0, 120.0, 280.0] {
+                for wall in [6.0, 10.0, 20.0] {
+                    let mut params = RingParameters::new(ring_type, inner_diameter + wall, inner_diameter).unwrap();
+                    for pad_tip in [PadTip::Flat, PadTip::CylindricalRidge, PadTip::SphericalBall] {
+                        params.pad_tip = pad_tip;
+                        let mesh = generate_ring_mesh(&params).unwrap();
+                        assert_closed_manifold(&mesh, &format!("{} {:?}", params.file_stem(), pad_tip));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lens_seats_touch_the_lens_at_the_contact_diameter() {
+        let rings = [(RingType::Convex, 60.0, 50.0, 52.5), (RingType::Concave, 60.0, 40.0, 54.0)];
+        for (ring_type, outer_diameter, inner_diameter, contact_diameter) in rings {
+            for surface in [LensSurface::Convex, LensSurface::Concave] {
+                let seat_types = [
+                    SeatType::Spherical,
+                    SeatType::Tangent,
+                    SeatType::SharpCorner,
+                    SeatType::Toroidal { radius: None },
+                    SeatType::Toroidal { radius: Some(0.4) },
+                ];
+                for seat_type in seat_types {
+                    for requested in [None, Some(contact_diameter)] {
+                        let mut params = RingParameters::with_defaults(ring_type, outer_diameter, inner_diameter);
+                        params.lens_seat = Some(LensSeat {
+                            radius_of_curvature: 80.0,
+                            surface,
+                            seat_type,
+                            contact_diameter: requested,
+                        });
+                        let label = format!("{} {} {} {:?}", params.file_stem(), surface, seat_type, requested);
+                        params.validate().unwrap_or_else(|e| panic!("{}: {}", label, e));
+
+                        let mesh = generate_ring_mesh(&params).unwrap();
+                        assert_closed_manifold(&mesh, &label);
+                        assert_seat_contact(&params, &mesh, requested, &label);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn default_torus_radius_fits_the_seat_step() {
+        for (outer_diameter, inner_diameter) in [(60.0, 50.0), (50.0, 40.0), (12.0, 8.0)] {
+            for height in [5.0, DEFAULT_HEIGHT] {
+                let mut params =
+                    RingParameters::with_defaults(RingType::Convex, outer_diameter, inner_diameter).with_height(height);
+                params.lens_seat = Some(LensSeat {
+                    radius_of_curvature: 80.0,
+                    surface: LensSurface::Convex,
+                    seat_type: "toroidal".parse().unwrap(),
+                    contact_diameter: None,
+                });
+                params.validate().unwrap_or_else(|e| panic!("{} at {}mm: {}", params.file_stem(), height, e));
+                let layout = params.seat_layout().unwrap();
+                assert_eq!(layout.torus_radius(), Some(params.inner_step_width * DEFAULT_TORUS_RADIUS_RATIO));
+            }
+        }
+    }
+
+    #[test]
+    fn chord_tolerance_sets_the_segment_count() {
+        // Smallest count whose chords stay within the tolerance
+        for (diameter, tolerance) in [(25.0f32, 0.05f32), (60.0, 0.05), (150.0, 0.1), (300.0, 0.05)] {
+            let radius = diameter / 2.0;
+            let segments = Tessellation::ChordTolerance(tolerance).segments_for_radius(radius);
+            let deviation = |n: usize| radius * (1.0 - (std::f32::consts::PI / n as f32).cos());
+            assert!(deviation(segments) <= tolerance * 1.001, "Ø{}: {} segments deviate too far", diameter, segments);
+            assert!(deviation(segments - 1) > tolerance, "Ø{}: {} segments are more than needed", diameter, segments);
+        }
+        assert_eq!(Tessellation::ChordTolerance(0.05).segments_for_radius(150.0), 122);
+        assert_eq!(Tessellation::AngularTolerance(10.0).segments_for_radius(150.0), 36);
+    }
+
+    #[test]
+    fn segment_count_is_clamped() {
+        assert_eq!(Tessellation::ChordTolerance(0.0001).segments_for_radius(150.0), MAX_SEGMENTS);
+        assert_eq!(Tessellation::ChordTolerance(1.0).segments_for_radius(3.0), MIN_SEGMENTS);
+        // A tolerance larger than the radius still gives a usable ring
+        assert_eq!(Tessellation::ChordTolerance(10.0).segments_for_radius(3.0), MIN_SEGMENTS);
+        assert_eq!(Tessellation::AngularTolerance(0.01).segments_for_radius(150.0), MAX_SEGMENTS);
+        assert_eq!(Tessellation::AngularTolerance(90.0).segments_for_radius(3.0), MIN_SEGMENTS);
+
+        let mut params = RingParameters::new(RingType::Concave, 300.0, 280.0).unwrap();
+        params.tessellation = Tessellation::ChordTolerance(0.0001);
+        assert_eq!(params.segment_count(), MAX_SEGMENTS);
+        let mut params = RingParameters::new(RingType::Concave, 6.0, 3.0).unwrap();
+        params.tessellation = Tessellation::ChordTolerance(1.0);
+        assert_eq!(params.segment_count(), MIN_SEGMENTS);
+    }
 }
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::geometry::RingParameters;
+use crate::mesh::Mesh;
+
+/// glTF 2.0 component types and buffer view targets
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+/// Turns our Z-up frame into the Y-up frame of glTF: -90° about X
+const Z_UP_TO_Y_UP: [f32; 4] = [-std::f32::consts::FRAC_1_SQRT_2, 0.0, 0.0, std::f32::consts::FRAC_1_SQRT_2];
+
+/// Binary glTF (GLB) of the ring. Positions stay in millimetres and Z-up; the
+/// node scales them to metres and turns them Y-up as glTF expects. No normals
+/// are written, so viewers shade the faces flat and sharp edges stay sharp.
+pub fn glb_document(mesh: &Mesh, params: &RingParameters) -> Result<Vec<u8>> {
+    let positions_length = mesh.vertices.len() * 12;
+    let indices_length = mesh.indices.len() * 12;
+    let mut binary = Vec::with_capacity(positions_length + indices_length);
+    for vertex in &mesh.vertices {
+        for value in vertex {
+            binary.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for triangle in &mesh.indices {
+        for index in triangle {
+            binary.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+
+    // Accessor bounds are required for positions
+    let (min, max) = mesh.vertices.iter().fold(
+        ([f32::MAX; 3], [f32::MIN; 3]),
+        |(min, max), v| ([0, 1, 2].map(|k| min[k].min(v[k])), [0, 1, 2].map(|k| max[k].max(v[k]))),
+    );
+    let extras: serde_json::Map<String, serde_json::Value> = params
+        .metadata()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.into()))
+        .collect();
+    let name = params.file_stem();
+
+    let document = json!({
+        "asset": { "version": "2.0", "generator": "Optics Ring Generator" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{
+            "name": name,
+            "mesh": 0,
+            "rotation": Z_UP_TO_Y_UP,
+            "scale": [0.001, 0.001, 0.001],
+            "extras": extras,
+        }],
+        "meshes": [{
+            "name": name,
+            "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1, "material": 0 }],
+        }],
+        "materials": [{
+            "name": "ring",
+            "pbrMetallicRoughness": { "baseColorFactor": [0.72, 0.76, 0.82, 1.0], "metallicFactor": 0.0, "roughnessFactor": 0.6 },
+        }],
+        "accessors": [
+            { "bufferView": 0, "componentType": FLOAT, "count": mesh.vertices.len(), "type": "VEC3", "min": min, "max": max },
+            { "bufferView": 1, "componentType": UNSIGNED_INT, "count": mesh.indices.len() * 3, "type": "SCALAR" },
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": positions_length, "target": ARRAY_BUFFER },
+            { "buffer": 0, "byteOffset": positions_length, "byteLength": indices_length, "target": ELEMENT_ARRAY_BUFFER },
+        ],
+        "buffers": [{ "byteLength": binary.len() }],
+    });
+
+    // Chunks are padded to 4 bytes: JSON with spaces, binary data with zeros
+    let mut json_chunk = serde_json::to_vec(&document)?;
+    json_chunk.resize(json_chunk.len().next_multiple_of(4), b' ');
+    binary.resize(binary.len().next_multiple_of(4), 0);
+
+    let total_length = 12 + 8 + json_chunk.len() + 8 + binary.len();
+    let mut glb = Vec::with_capacity(total_length);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total_length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json_chunk);
+    glb.extend_from_slice(&(binary.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&binary);
+
+    Ok(glb)
+}
@@ -0,0 +1,134 @@
+// Async commands that generate rings on worker threads, so the window stays
+// responsive, report progress to it and can be cancelled by job id
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use tauri::{Emitter, State, Window};
+
+use crate::batch::{run_job_file, BatchProgress, BatchReport, CancelToken};
+use crate::commands;
+use crate::sweep::{run_sweep, Sweep, SweepAxis, SweepLayout};
+use crate::{RingRequest, RingResponse};
+
+/// Event sent to the window after each ring of a job
+const PROGRESS_EVENT: &str = "generation-progress";
+
+/// Cancel tokens of the running jobs, by the job id the frontend chose
+#[derive(Default)]
+pub struct RunningJobs(Mutex<HashMap<String, CancelToken>>);
+
+impl RunningJobs {
+    fn start(&self, job_id: &str) -> CancelToken {
+        let cancel = CancelToken::default();
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).insert(job_id.to_string(), cancel.clone());
+        cancel
+    }
+
+    fn finish(&self, job_id: &str) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).remove(job_id);
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct ProgressEvent<'a> {
+    job_id: &'a str,
+    #[serde(flatten)]
+    progress: BatchProgress,
+}
+
+fn emit_progress(window: &Window, job_id: &str, progress: BatchProgress) {
+    if let Err(e) = window.emit(PROGRESS_EVENT, ProgressEvent { job_id, progress }) {
+        eprintln!("Failed to send progress: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn generate_ring(
+    window: Window,
+    jobs: State<'_, RunningJobs>,
+    job_id: String,
+    request: RingRequest,
+) -> Result<RingResponse, String> {
+    let cancel = jobs.start(&job_id);
+    emit_progress(&window, &job_id, BatchProgress { rings_done: 0, rings_total: 1, triangles_written: 0 });
+    let response = tauri::async_runtime::spawn_blocking(move || commands::generate_ring(request, &cancel)).await;
+    jobs.finish(&job_id);
+    let response = response.map_err(|e| format!("Failed to generate ring: {}", e))?;
+
+    let triangles_written = match (&response.mesh_check, response.success) {
+        (Some(report), true) => report.triangle_count,
+        _ => 0,
+    };
+    emit_progress(&window, &job_id, BatchProgress { rings_done: 1, rings_total: 1, triangles_written });
+    Ok(response)
+}
+
+/// Generate every ring of a CSV or JSON job file as an STL file, with a report
+/// for each row
+#[tauri::command]
+pub async fn generate_batch(
+    window: Window,
+    jobs: State<'_, RunningJobs>,
+    job_id: String,
+    path: String,
+    output_path: Option<String>,
+) -> Result<BatchReport, String> {
+    let cancel = jobs.start(&job_id);
+    let id = job_id.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        run_job_file(&path, output_path.as_deref(), &cancel, |progress| emit_progress(&window, &id, progress))
+    })
+    .await;
+    jobs.finish(&job_id);
+
+    report
+        .map_err(|e| format!("Failed to run batch: {}", e))?
+        .map_err(|e| format!("Failed to run batch: {}", e))
+}
+
+/// Generate a family of rings around the request's ring, as separate files or
+/// one labelled plate STL
+#[tauri::command]
+pub async fn generate_sweep(
+    window: Window,
+    jobs: State<'_, RunningJobs>,
+    job_id: String,
+    request: RingRequest,
+    sweep: Vec<SweepAxis>,
+    layout: Option<String>,
+) -> Result<BatchReport, String> {
+    let base = request.to_parameters()?;
+    let format = request.output_format()?;
+    let layout = match layout {
+        Some(layout) => layout.parse::<SweepLayout>().map_err(|e| e.to_string())?,
+        None => SweepLayout::default(),
+    };
+    let output_dir = request.output_path().map(str::to_string);
+    let sweep = Sweep { base, axes: sweep };
+
+    let cancel = jobs.start(&job_id);
+    let id = job_id.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        run_sweep(&sweep, layout, format, output_dir.as_deref(), &cancel, |progress| emit_progress(&window, &id, progress))
+    })
+    .await;
+    jobs.finish(&job_id);
+
+    report
+        .map_err(|e| format!("Failed to run sweep: {}", e))?
+        .map_err(|e| format!("Failed to run sweep: {}", e))
+}
+
+/// Ask a running job to stop. Returns false when no job has this id.
+#[tauri::command]
+pub fn cancel_job(jobs: State<'_, RunningJobs>, job_id: String) -> bool {
+    let jobs = jobs.0.lock().unwrap_or_else(PoisonError::into_inner);
+    match jobs.get(&job_id) {
+        Some(cancel) => {
+            cancel.cancel();
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+
+use crate::mesh::Mesh;
+
+/// Character width as a fraction of the text height
+const CHAR_WIDTH: f32 = 0.55;
+/// Stroke width as a fraction of the text height
+const STROKE: f32 = 0.14;
+/// Space between characters as a fraction of the text height
+const CHAR_SPACING: f32 = 0.2;
+
+/// Lit segments of a seven-segment digit, bits a to g: top, top right,
+/// bottom right, bottom, bottom left, top left, middle
+fn segments(c: char) -> Option<u8> {
+    Some(match c {
+        '0' => 0b0111111,
+        '1' => 0b0000110,
+        '2' => 0b1011011,
+        '3' => 0b1001111,
+        '4' => 0b1100110,
+        '5' => 0b1101101,
+        '6' => 0b1111101,
+        '7' => 0b0000111,
+        '8' => 0b1111111,
+        '9' => 0b1101111,
+        '-' => 0b1000000,
+        ' ' => 0,
+        _ => return None,
+    })
+}
+
+/// Advance of one character; a decimal point only takes a stroke and a gap
+fn advance(c: char, height: f32) -> f32 {
+    match c {
+        '.' => (STROKE + CHAR_SPACING) * height,
+        _ => (CHAR_WIDTH + CHAR_SPACING) * height,
+    }
+}
+
+/// Width of a label of the given text height, without the trailing gap
+pub fn label_width(text: &str, height: f32) -> f32 {
+    (text.chars().map(|c| advance(c, height)).sum::<f32>() - CHAR_SPACING * height).max(0.0)
+}
+
+/// Raised seven-segment text standing on z = 0, `depth` high, with its lower
+/// left corner at `origin`. Each segment is a box, so strokes may overlap;
+/// slicers merge the shells. Digits, '-', '.' and spaces are supported.
+pub fn label_mesh(text: &str, origin: [f32; 2], height: f32, depth: f32) -> Result<Mesh> {
+    let stroke = STROKE * height;
+    let width = CHAR_WIDTH * height;
+    let half = height / 2.0;
+    let mut mesh = Mesh::new();
+
+    let mut x = origin[0];
+    for c in text.chars() {
+        let y = origin[1];
+        if c == '.' {
+            add_box(&mut mesh, [x, y], [x + stroke, y + stroke], depth);
+        } else {
+            let Some(lit) = segments(c) else {
+                bail!("Cannot write '{}' in a label", c);
+            };
+            // Segment boxes as (x0, y0, x1, y1) relative to the character
+            let boxes = [
+                (0.0, height - stroke, width, height),
+                (width - stroke, half, width, height),
+                (width - stroke, 0.0, width, half),
+                (0.0, 0.0, width, stroke),
+                (0.0, 0.0, stroke, half),
+                (0.0, half, stroke, height),
+                (0.0, half - stroke / 2.0, width, half + stroke / 2.0),
+            ];
+            for (bit, (x0, y0, x1, y1)) in boxes.into_iter().enumerate() {
+                if lit & (1 << bit) != 0 {
+                    add_box(&mut mesh, [x + x0, y + y0], [x + x1, y + y1], depth);
+                }
+            }
+        }
+        x += advance(c, height);
+    }
+    Ok(mesh)
+}
+
+/// Closed box from z = 0 to `depth` over the rectangle `min`–`max`, wound outwards
+fn add_box(mesh: &mut Mesh, min: [f32; 2], max: [f32; 2], depth: f32) {
+    let corners = [[min[0], min[1]], [max[0], min[1]], [max[0], max[1]], [min[0], max[1]]];
+    let bottom: Vec<u32> = corners.iter().map(|&[x, y]| mesh.add_vertex([x, y, 0.0])).collect();
+    let top: Vec<u32> = corners.iter().map(|&[x, y]| mesh.add_vertex([x, y, depth])).collect();
+
+    mesh.add_triangle(bottom[0], bottom[2], bottom[1]);
+    mesh.add_triangle(bottom[0], bottom[3], bottom[2]);
+    mesh.add_triangle(top[0], top[1], top[2]);
+    mesh.add_triangle(top[0], top[2], top[3]);
+    for i in 0..4 {
+        let j = (i + 1) % 4;
+        mesh.add_triangle(bottom[i], bottom[j], top[j]);
+        mesh.add_triangle(bottom[i], top[j], top[i]);
+    }
+}
@@ -1,26 +1,296 @@
 // Modules for optics ring generation
-mod geometry;
-mod stl_output;
+pub mod batch;
+pub mod commands;
+pub mod drawing;
+pub mod geometry;
+pub mod gltf_output;
+#[cfg(feature = "gui")]
+mod jobs;
+pub mod label;
+pub mod manufacturing;
+pub mod mesh;
+pub mod mesh_check;
+pub mod mesh_stats;
+pub mod profile_output;
+pub mod server;
+pub mod spec;
+pub mod step_output;
+pub mod stl_output;
+pub mod sweep;
+pub mod three_mf;
+pub mod thumbnail;
+pub mod timestamp;
+pub mod xml;
 
-use geometry::{RingType, RingParameters};
-use stl_output::generate_stl_file;
+use drawing::DrawingFormat;
+use geometry::{LensSeat, LensSurface, PadTip, RingType, RingParameters, SeatType, Tessellation};
+use manufacturing::ManufacturingReport;
+use mesh_check::MeshReport;
+use stl_output::OutputFormat;
+use serde::de::IntoDeserializer;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
-pub struct MeshData {
-    vertices: Vec<f32>,  // Flattened vertex data [x,y,z,x,y,z,...]
-    triangles: Vec<u32>, // Flattened triangle indices [i1,i2,i3,i1,i2,i3,...]
-    vertex_count: usize,
-    triangle_count: usize,
+impl From<&RingParameters> for RingRequest {
+    /// Request that regenerates the given ring
+    fn from(params: &RingParameters) -> Self {
+        let seat = params.lens_seat;
+        let (chord_tolerance, angular_tolerance) = match params.tessellation {
+            Tessellation::ChordTolerance(tolerance) => (Some(tolerance), None),
+            Tessellation::AngularTolerance(degrees) => (None, Some(degrees)),
+        };
+        RingRequest {
+            ring_type: params.ring_type.to_string(),
+            outer_diameter: params.outer_diameter,
+            inner_diameter: params.inner_diameter,
+            height: Some(params.height),
+            top_step_height: Some(params.top_step_height),
+            bottom_step_height: Some(params.bottom_step_height),
+            middle_step_height: Some(params.middle_step_height),
+            inner_step_width: Some(params.inner_step_width),
+            chamfer_depth: Some(params.chamfer_depth),
+            lens_radius: seat.map(|s| s.radius_of_curvature),
+            lens_surface: seat.map(|s| s.surface.to_string()),
+            seat_type: seat.map(|s| s.seat_type.to_string()),
+            torus_radius: seat.and_then(|s| match s.seat_type {
+                SeatType::Toroidal { radius } => radius,
+                _ => None,
+            }),
+            contact_diameter: seat.and_then(|s| s.contact_diameter),
+            contact_height: None,
+            contact_count: Some(params.contact_count),
+            contact_offset: Some(params.contact_offset_deg),
+            pad_radius: params.pad_radius,
+            pad_width: Some(params.pad_width),
+            pad_height: Some(params.pad_height),
+            pad_tip: Some(params.pad_tip.to_string()),
+            chord_tolerance,
+            angular_tolerance,
+            output_path: None,
+            format: None,
+            drawing: None,
+        }
+    }
 }
 
-// Data structures for frontend communication
+// Data structures for frontend communication. Numeric fields also take their
+// value as text, the way command-line options arrive.
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct RingRequest {
     ring_type: String,
+    #[serde(deserialize_with = "number")]
     outer_diameter: f32,
+    #[serde(deserialize_with = "number")]
     inner_diameter: f32,
+    #[serde(default, deserialize_with = "optional_number")]
+    height: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    top_step_height: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    bottom_step_height: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    middle_step_height: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    inner_step_width: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    chamfer_depth: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    lens_radius: Option<f32>,       // Lens surface radius of curvature; enables the lens seat
+    lens_surface: Option<String>,   // "convex" or "concave"; follows the ring type by default
+    seat_type: Option<String>,      // "spherical" (default), "tangent", "sharp-corner" or "toroidal"
+    #[serde(default, deserialize_with = "optional_number")]
+    torus_radius: Option<f32>,      // Section radius of a toroidal seat
+    #[serde(default, deserialize_with = "optional_number")]
+    contact_diameter: Option<f32>,
+    #[serde(default, deserialize_with = "optional_number")]
+    contact_height: Option<f32>,    // Contact height on the lens (distance from the axis), instead of the diameter
+    #[serde(default, deserialize_with = "optional_number")]
+    contact_count: Option<u32>,     // Number of pads of a point-contact (3P) ring, e.g. 6 for a 6P ring
+    #[serde(default, deserialize_with = "optional_number")]
+    contact_offset: Option<f32>,    // Angle of the first pad, in degrees
+    #[serde(default, deserialize_with = "optional_number")]
+    pad_radius: Option<f32>,        // Distance of the pad centers from the axis
+    #[serde(default, deserialize_with = "optional_number")]
+    pad_width: Option<f32>,         // Point-contact pad width
+    #[serde(default, deserialize_with = "optional_number")]
+    pad_height: Option<f32>,        // Point-contact pad height above the base annulus
+    pad_tip: Option<String>,        // "flat" (default), "ridge" or "ball"
+    #[serde(default, deserialize_with = "optional_number")]
+    chord_tolerance: Option<f32>,   // Maximum chordal deviation of the tessellation (mm)
+    #[serde(default, deserialize_with = "optional_number")]
+    angular_tolerance: Option<f32>, // Maximum angle per segment (degrees), instead of the chord tolerance
     output_path: Option<String>,
+    format: Option<String>,         // "stl" (default), "stl-ascii", "3mf", "obj", "ply", "ply-ascii", "glb", "step", or the "dxf"/"svg" cross-section
+    drawing: Option<String>,        // "svg" or "pdf" to also save a dimensioned drawing next to the ring file
+}
+
+/// A request number, given as a JSON number or as text such as `"24.8"`
+struct Number<T>(T);
+
+impl<'de, T: Deserialize<'de> + std::str::FromStr> Deserialize<'de> for Number<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NumberVisitor(std::marker::PhantomData)).map(Number)
+    }
+}
+
+struct NumberVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de> + std::str::FromStr> serde::de::Visitor<'de> for NumberVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a number")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<T, E> {
+        T::deserialize(value.into_deserializer())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<T, E> {
+        T::deserialize(value.into_deserializer())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<T, E> {
+        T::deserialize(value.into_deserializer())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<T, E> {
+        // Plain decimal notation only, so "inf" and "NaN" are refused
+        Some(text.trim())
+            .filter(|text| text.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c)))
+            .and_then(|text| text.parse().ok())
+            .ok_or_else(|| E::custom(format!("invalid number \"{}\"", text)))
+    }
+}
+
+fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + std::str::FromStr,
+{
+    Number::deserialize(deserializer).map(|number| number.0)
+}
+
+fn optional_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + std::str::FromStr,
+{
+    Option::<Number<T>>::deserialize(deserializer).map(|number| number.map(|number| number.0))
+}
+
+impl RingRequest {
+    /// Parse and validate the request into ring parameters
+    pub fn to_parameters(&self) -> Result<RingParameters, String> {
+        let ring_type = self.ring_type.parse::<RingType>()
+            .map_err(|e| format!("Invalid ring type: {}", e))?;
+
+        // Defaults first, then every override, then a single validation of the result
+        let mut params = RingParameters::with_defaults(ring_type, self.outer_diameter, self.inner_diameter);
+        if let Some(height) = self.height {
+            params = params.with_height(height);
+        }
+        params.top_step_height = self.top_step_height.unwrap_or(params.top_step_height);
+        params.bottom_step_height = self.bottom_step_height.unwrap_or(params.bottom_step_height);
+        params.middle_step_height = self.middle_step_height.unwrap_or(params.middle_step_height);
+        params.inner_step_width = self.inner_step_width.unwrap_or(params.inner_step_width);
+        params.chamfer_depth = self.chamfer_depth.unwrap_or(params.chamfer_depth);
+        params.contact_count = self.contact_count.unwrap_or(params.contact_count);
+        params.contact_offset_deg = self.contact_offset.unwrap_or(params.contact_offset_deg);
+        params.pad_radius = self.pad_radius.or(params.pad_radius);
+        params.pad_width = self.pad_width.unwrap_or(params.pad_width);
+        params.pad_height = self.pad_height.unwrap_or(params.pad_height);
+        if let Some(pad_tip) = &self.pad_tip {
+            params.pad_tip = pad_tip.parse::<PadTip>()
+                .map_err(|e| format!("Invalid parameters: {}", e))?;
+        }
+        params.tessellation = match (self.chord_tolerance, self.angular_tolerance) {
+            (Some(_), Some(_)) => {
+                return Err("Invalid parameters: give either a chord or an angular tolerance, not both".to_string())
+            }
+            (Some(tolerance), None) => Tessellation::ChordTolerance(tolerance),
+            (None, Some(degrees)) => Tessellation::AngularTolerance(degrees),
+            (None, None) => params.tessellation,
+        };
+        if let Some(radius_of_curvature) = self.lens_radius {
+            params.lens_seat = Some(self.lens_seat(ring_type, radius_of_curvature)?);
+        }
+        params.validate()
+            .map_err(|e| format!("Invalid parameters: {}", e))?;
+
+        Ok(params)
+    }
+
+    pub fn output_path(&self) -> Option<&str> {
+        self.output_path.as_deref()
+    }
+
+    pub fn output_format(&self) -> Result<OutputFormat, String> {
+        match self.format.as_deref() {
+            Some(s) => s.parse::<OutputFormat>().map_err(|e| e.to_string()),
+            None => Ok(OutputFormat::default()),
+        }
+    }
+
+    pub fn drawing_format(&self) -> Result<Option<DrawingFormat>, String> {
+        self.drawing.as_deref()
+            .map(|s| s.parse::<DrawingFormat>().map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    /// The request without the fields that repeat the default of a ring of its
+    /// type, size and height, leaving what was chosen for this ring
+    pub fn without_defaults(mut self) -> Self {
+        let Ok(ring_type) = self.ring_type.parse::<RingType>() else {
+            return self;
+        };
+        let mut defaults = RingParameters::with_defaults(ring_type, self.outer_diameter, self.inner_diameter);
+        if let Some(height) = self.height {
+            defaults = defaults.with_height(height);
+        }
+        let defaults = RingRequest::from(&defaults);
+
+        fn clear_default<T: PartialEq>(value: &mut Option<T>, default: Option<T>) {
+            if *value == default {
+                *value = None;
+            }
+        }
+        clear_default(&mut self.top_step_height, defaults.top_step_height);
+        clear_default(&mut self.bottom_step_height, defaults.bottom_step_height);
+        clear_default(&mut self.middle_step_height, defaults.middle_step_height);
+        clear_default(&mut self.inner_step_width, defaults.inner_step_width);
+        clear_default(&mut self.chamfer_depth, defaults.chamfer_depth);
+        clear_default(&mut self.contact_count, defaults.contact_count);
+        clear_default(&mut self.contact_offset, defaults.contact_offset);
+        clear_default(&mut self.pad_width, defaults.pad_width);
+        clear_default(&mut self.pad_height, defaults.pad_height);
+        clear_default(&mut self.pad_tip, defaults.pad_tip);
+        clear_default(&mut self.chord_tolerance, defaults.chord_tolerance);
+        self
+    }
+
+    fn lens_seat(&self, ring_type: RingType, radius_of_curvature: f32) -> Result<LensSeat, String> {
+        let surface = match self.lens_surface.as_deref() {
+            Some(s) => s.parse::<LensSurface>().map_err(|e| e.to_string())?,
+            None if ring_type == RingType::Concave => LensSurface::Concave,
+            None => LensSurface::Convex,
+        };
+        let mut seat_type = match (self.seat_type.as_deref(), self.torus_radius) {
+            (Some(s), _) => s.parse::<SeatType>().map_err(|e| e.to_string())?,
+            (None, Some(_)) => SeatType::Toroidal { radius: None },
+            (None, None) => SeatType::Spherical,
+        };
+        if let (SeatType::Toroidal { radius }, Some(torus_radius)) = (&mut seat_type, self.torus_radius) {
+            *radius = Some(torus_radius);
+        }
+
+        Ok(LensSeat {
+            radius_of_curvature,
+            surface,
+            seat_type,
+            contact_diameter: self.contact_diameter.or(self.contact_height.map(|h| h * 2.0)),
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -29,125 +299,127 @@ pub struct RingResponse {
     message: String,
     filename: Option<String>,
     file_path: Option<String>,
+    mesh_check: Option<MeshReport>,
+    contact_diameter: Option<f32>, // Where the lens touches the seat, for CX/CC rings with a lens seat
+    manufacturing: Option<ManufacturingReport>,
+    drawing_path: Option<String>,
 }
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+#[cfg(feature = "gui")]
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-#[tauri::command]
-fn generate_ring(request: RingRequest) -> RingResponse {
-    // Parse ring type
-    let ring_type = match request.ring_type.parse::<RingType>() {
-        Ok(rt) => rt,
-        Err(e) => return RingResponse {
-            success: false,
-            message: format!("Invalid ring type: {}", e),
-            filename: None,
-            file_path: None,
-        }
-    };
-
-    // Create ring parameters
-    let params = match RingParameters::new(ring_type, request.outer_diameter, request.inner_diameter) {
-        Ok(p) => p,
-        Err(e) => return RingResponse {
-            success: false,
-            message: format!("Invalid parameters: {}", e),
-            filename: None,
-            file_path: None,
-        }
-    };
-
-    // Determine output directory
-    let output_dir = request.output_path.as_deref();
-
-    // Generate STL file
-    match generate_stl_file(&params, output_dir) {
-        Ok(file_path) => RingResponse {
-            success: true,
-            message: format!("Successfully generated {} ring", ring_type),
-            filename: Some(params.filename()),
-            file_path: Some(file_path),
-        },
-        Err(e) => RingResponse {
-            success: false,
-            message: format!("Failed to generate STL: {}", e),
-            filename: None,
-            file_path: None,
-        }
-    }
-}
-
-#[tauri::command]
-fn generate_mesh_preview(request: RingRequest) -> Result<MeshData, String> {
-    println!("Starting mesh preview generation with request: {:?}", request);
-    
-    // Parse ring type
-    let ring_type = match request.ring_type.parse::<RingType>() {
-        Ok(rt) => rt,
-        Err(e) => return Err(format!("Invalid ring type: {}", e))
-    };
-    println!("Parsed ring type: {:?}", ring_type);
-
-    // Create ring parameters
-    let params = match RingParameters::new(ring_type, request.outer_diameter, request.inner_diameter) {
-        Ok(p) => p,
-        Err(e) => return Err(format!("Invalid parameters: {}", e))
-    };
-    println!("Created ring parameters successfully");
-
-    // Generate mesh
-    let mesh = match geometry::generate_ring_mesh(&params) {
-        Ok(m) => m,
-        Err(e) => return Err(format!("Failed to generate mesh: {}", e))
-    };
-    println!("Generated mesh with {} triangles", mesh.len());
-
-    // Convert mesh to format suitable for Three.js
-    let mut vertices = Vec::new();
-    let mut triangles = Vec::new();
-
-    for (i, triangle) in mesh.iter().enumerate() {
-        let base_index = (i * 3) as u32;
-        
-        // Add vertices as flat array [x,y,z,x,y,z,...]
-        vertices.extend_from_slice(&[triangle.vertices[0][0], triangle.vertices[0][1], triangle.vertices[0][2]]);
-        vertices.extend_from_slice(&[triangle.vertices[1][0], triangle.vertices[1][1], triangle.vertices[1][2]]);
-        vertices.extend_from_slice(&[triangle.vertices[2][0], triangle.vertices[2][1], triangle.vertices[2][2]]);
-        
-        // Add triangle indices as flat array [i1,i2,i3,i1,i2,i3,...]
-        triangles.extend_from_slice(&[base_index, base_index + 1, base_index + 2]);
-    }
-
-    let result = MeshData {
-        vertex_count: vertices.len() / 3,  // Number of vertices (3 floats per vertex)
-        triangle_count: triangles.len() / 3, // Number of triangles (3 indices per triangle)
-        vertices,
-        triangles,
-    };
-    
-    println!("Created MeshData - vertex_count: {}, triangle_count: {}, vertices.len(): {}, triangles.len(): {}", 
-             result.vertex_count, result.triangle_count, result.vertices.len(), result.triangles.len());
-    
-    if result.vertices.len() > 0 {
-        println!("First few vertices: {:?}", &result.vertices[0..result.vertices.len().min(9)]);
-    }
-    if result.triangles.len() > 0 {
-        println!("First few triangles: {:?}", &result.triangles[0..result.triangles.len().min(9)]);
-    }
-    
-    Ok(result)
-}
-
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![greet, generate_ring, generate_mesh_preview])
+        .manage(jobs::RunningJobs::default())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            jobs::generate_ring,
+            jobs::generate_batch,
+            jobs::generate_sweep,
+            jobs::cancel_job,
+            commands::generate_mesh_preview,
+            commands::get_mesh_stats,
+            commands::analyze_ring,
+            commands::read_ring_parameters,
+            commands::read_ring_spec,
+            commands::save_ring_spec,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(fields: serde_json::Value) -> RingRequest {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    #[test]
+    fn overrides_apply_before_validation() {
+        // The default chamfer does not fit a 2.5mm ring, the requested one does
+        let params = request(json!({
+            "ring_type": "CX", "outer_diameter": 60, "inner_diameter": 50, "height": 2.5, "chamfer_depth": 0.3,
+        }))
+        .to_parameters()
+        .unwrap();
+        assert_eq!(params.chamfer_depth, 0.3);
+        assert_eq!(params.height, 2.5);
+
+        let error = request(json!({
+            "ring_type": "CX", "outer_diameter": 60, "inner_diameter": 50, "height": 2.5, "chamfer_depth": 1.3,
+        }))
+        .to_parameters()
+        .unwrap_err();
+        assert!(error.contains("1.3mm"), "{}", error);
+    }
+
+    #[test]
+    fn pad_overrides_apply_before_validation() {
+        // The default 2mm pads are as high as this ring, the requested ones are not
+        let params = request(json!({
+            "ring_type": "3P", "outer_diameter": 60, "inner_diameter": 40, "height": 2,
+            "pad_height": 1, "pad_width": 3, "pad_radius": 24,
+        }))
+        .to_parameters()
+        .unwrap();
+        assert_eq!((params.pad_height, params.pad_width, params.pad_radius), (1.0, 3.0, Some(24.0)));
+
+        let error = request(json!({
+            "ring_type": "3P", "outer_diameter": 60, "inner_diameter": 40, "height": 2, "pad_height": 2.5,
+        }))
+        .to_parameters()
+        .unwrap_err();
+        assert!(error.contains("2.5mm"), "{}", error);
+    }
+
+    #[test]
+    fn without_defaults_keeps_only_chosen_fields() {
+        let mut params = RingParameters::new(RingType::ThreePoint, 60.0, 40.0).unwrap();
+        params.contact_count = 6;
+        let fields = serde_json::to_value(RingRequest::from(&params).without_defaults()).unwrap();
+        let chosen: Vec<&str> = fields
+            .as_object()
+            .unwrap()
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(chosen, ["contact_count", "height", "inner_diameter", "outer_diameter", "ring_type"]);
+
+        let request = request(json!({
+            "ring_type": "CX", "outer_diameter": 60, "inner_diameter": 50, "height": 2.5, "chamfer_depth": 0.3,
+        }));
+        let params = request.to_parameters().unwrap();
+        let request = RingRequest::from(&params).without_defaults();
+        assert_eq!(request.chamfer_depth, Some(0.3));
+        assert_eq!(request.top_step_height, None);
+    }
+
+    #[test]
+    fn numeric_fields_read_numbers_from_text() {
+        let params = request(json!({
+            "ring_type": "3P", "outer_diameter": "60", "inner_diameter": 40, "contact_count": "6", "pad_width": " 3.5",
+        }))
+        .to_parameters()
+        .unwrap();
+        assert_eq!((params.outer_diameter, params.contact_count, params.pad_width), (60.0, 6, 3.5));
+
+        let fields = json!({ "ring_type": "CX", "outer_diameter": 60, "inner_diameter": 50, "height": "NaN" });
+        let error = serde_json::from_value::<RingRequest>(fields).unwrap_err().to_string();
+        assert!(error.contains("invalid number \"NaN\""), "{}", error);
+        let fields = json!({ "ring_type": "CX", "outer_diameter": true, "inner_diameter": 50 });
+        let error = serde_json::from_value::<RingRequest>(fields).unwrap_err().to_string();
+        assert!(error.contains("invalid type: boolean `true`, expected a number"), "{}", error);
+    }
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::{PadTip, RingParameters, RingType};
+use crate::mesh::Mesh;
+use crate::mesh_check::MeshReport;
+use crate::mesh_stats::{compute_mesh_stats, MeshStats};
+
+/// Thinnest wall that prints reliably (mm)
+const MIN_WALL_THICKNESS: f32 = 1.0;
+/// Largest outer diameter that fits a typical printer bed (mm)
+const MAX_PRINTABLE_DIAMETER: f32 = 300.0;
+/// Smallest bore that is still practical to use (mm)
+const MIN_INNER_DIAMETER: f32 = 5.0;
+/// Features narrower than this print poorly with a 0.4mm nozzle (mm)
+const MIN_FEATURE_WIDTH: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The part will probably print, but check it
+    Warning,
+    /// The part is not fit for printing as it is
+    Error,
+}
+
+/// One finding of the manufacturing analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManufacturingIssue {
+    /// Stable identifier, e.g. `WALL_TOO_THIN`
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Suggested slicer settings for the ring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrintRecommendations {
+    pub layer_height: String,
+    pub supports: String,
+    pub orientation: String,
+    pub material: String,
+    pub infill: String,
+}
+
+/// Printability of a ring: issues found, measured material use and print settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManufacturingReport {
+    /// No issue of error severity was found
+    pub printable: bool,
+    pub wall_thickness: f32,
+    pub stats: MeshStats,
+    pub issues: Vec<ManufacturingIssue>,
+    pub recommendations: PrintRecommendations,
+}
+
+impl ManufacturingReport {
+    fn push(&mut self, code: &str, severity: Severity, message: String) {
+        self.issues.push(ManufacturingIssue {
+            code: code.to_string(),
+            severity,
+            message,
+        });
+    }
+}
+
+/// Check a ring and its mesh for 3D printing and recommend print settings
+pub fn analyze_for_printing(params: &RingParameters, mesh: &Mesh, mesh_report: &MeshReport) -> ManufacturingReport {
+    let wall_thickness = (params.outer_diameter - params.inner_diameter) / 2.0;
+    let mut report = ManufacturingReport {
+        printable: true,
+        wall_thickness,
+        stats: compute_mesh_stats(mesh),
+        issues: Vec::new(),
+        recommendations: recommend_settings(params),
+    };
+
+    if wall_thickness < MIN_WALL_THICKNESS {
+        report.push("WALL_TOO_THIN", Severity::Error, format!(
+            "Wall thickness ({:.2}mm) is too thin for reliable 3D printing. Minimum recommended: {:.1}mm",
+            wall_thickness, MIN_WALL_THICKNESS
+        ));
+    }
+    if params.outer_diameter > MAX_PRINTABLE_DIAMETER {
+        report.push("EXCEEDS_BUILD_VOLUME", Severity::Error, format!(
+            "Outer diameter ({:.1}mm) exceeds typical 3D printer build volume",
+            params.outer_diameter
+        ));
+    }
+    if params.inner_diameter < MIN_INNER_DIAMETER {
+        report.push("BORE_TOO_SMALL", Severity::Error, format!(
+            "Inner diameter ({:.1}mm) is too small for practical use",
+            params.inner_diameter
+        ));
+    }
+    if !mesh_report.is_valid {
+        report.push("MESH_INVALID", Severity::Error, format!(
+            "The mesh check found problems: {}",
+            mesh_report.issues.join("; ")
+        ));
+    }
+
+    // Narrow features that may not print cleanly
+    match params.ring_type {
+        RingType::Convex if params.inner_step_width < MIN_FEATURE_WIDTH => {
+            report.push("THIN_INNER_STEP", Severity::Warning, format!(
+                "Inner step ({:.2}mm) is narrower than {:.1}mm and may print poorly",
+                params.inner_step_width, MIN_FEATURE_WIDTH
+            ));
+        }
+        RingType::ThreePoint if params.pad_width < MIN_FEATURE_WIDTH => {
+            report.push("THIN_PAD", Severity::Warning, format!(
+                "Contact pads ({:.2}mm) are narrower than {:.1}mm and may print poorly",
+                params.pad_width, MIN_FEATURE_WIDTH
+            ));
+        }
+        _ => {}
+    }
+
+    report.printable = report.issues.iter().all(|issue| issue.severity != Severity::Error);
+    report
+}
+
+fn recommend_settings(params: &RingParameters) -> PrintRecommendations {
+    let (layer_height, supports) = match params.ring_type {
+        RingType::Convex => ("0.15-0.2mm for smooth curves", "None required"),
+        RingType::Concave => ("0.15-0.2mm for smooth curves", "Light support for overhangs"),
+        // Curved pad tips need finer layers to keep their shape
+        RingType::ThreePoint if params.pad_tip != PadTip::Flat => ("0.1-0.15mm for the curved pad tips", "None required"),
+        RingType::ThreePoint => ("0.2-0.3mm (structural print)", "None required"),
+    };
+
+    PrintRecommendations {
+        layer_height: layer_height.to_string(),
+        supports: supports.to_string(),
+        orientation: "Place flat on build plate".to_string(),
+        material: "PLA or PETG for optical applications".to_string(),
+        infill: "100% for maximum stability".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::generate_ring_mesh;
+    use crate::mesh_check::check_mesh;
+
+    fn analyze(params: &RingParameters) -> ManufacturingReport {
+        let mesh = generate_ring_mesh(params).unwrap();
+        analyze_for_printing(params, &mesh, &check_mesh(&mesh))
+    }
+
+    fn codes(report: &ManufacturingReport) -> Vec<&str> {
+        report.issues.iter().map(|issue| issue.code.as_str()).collect()
+    }
+
+    #[test]
+    fn default_rings_are_printable() {
+        for ring_type in RingType::ALL {
+            let report = analyze(&RingParameters::new(ring_type, 50.0, 40.0).unwrap());
+            assert!(report.printable, "{}: {:?}", ring_type, codes(&report));
+            assert!(report.issues.is_empty(), "{}: {:?}", ring_type, codes(&report));
+            assert_eq!(report.wall_thickness, 5.0);
+            assert!(report.stats.volume > 0.0);
+        }
+    }
+
+    #[test]
+    fn thin_wall_is_an_error() {
+        let report = analyze(&RingParameters::with_defaults(RingType::Concave, 40.0, 38.6));
+        assert_eq!(codes(&report), ["WALL_TOO_THIN"]);
+        assert_eq!(report.issues[0].severity, Severity::Error);
+        assert!(!report.printable);
+    }
+
+    #[test]
+    fn oversized_ring_is_an_error() {
+        let report = analyze(&RingParameters::new(RingType::Concave, 320.0, 300.0).unwrap());
+        assert_eq!(codes(&report), ["EXCEEDS_BUILD_VOLUME"]);
+        assert!(!report.printable);
+    }
+
+    #[test]
+    fn small_bore_is_an_error() {
+        let report = analyze(&RingParameters::new(RingType::Concave, 12.0, 4.0).unwrap());
+        assert_eq!(codes(&report), ["BORE_TOO_SMALL"]);
+        assert!(!report.printable);
+    }
+
+    #[test]
+    fn invalid_mesh_is_an_error() {
+        let params = RingParameters::new(RingType::Concave, 50.0, 40.0).unwrap();
+        let mut mesh = generate_ring_mesh(&params).unwrap();
+        mesh.indices.pop();
+        let report = analyze_for_printing(&params, &mesh, &check_mesh(&mesh));
+        assert_eq!(codes(&report), ["MESH_INVALID"]);
+        assert!(!report.printable);
+    }
+
+    #[test]
+    fn thin_inner_step_is_a_warning() {
+        let mut params = RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap();
+        params.inner_step_width = 0.6;
+        let report = analyze(&params);
+        assert_eq!(codes(&report), ["THIN_INNER_STEP"]);
+        assert_eq!(report.issues[0].severity, Severity::Warning);
+        assert!(report.printable);
+    }
+
+    #[test]
+    fn thin_pad_is_a_warning() {
+        let mut params = RingParameters::new(RingType::ThreePoint, 50.0, 40.0).unwrap();
+        params.pad_width = 0.8;
+        let report = analyze(&params);
+        assert_eq!(codes(&report), ["THIN_PAD"]);
+        assert_eq!(report.issues[0].severity, Severity::Warning);
+        assert!(report.printable);
+    }
+}
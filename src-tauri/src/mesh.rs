@@ -0,0 +1,84 @@
+use nalgebra::{Point3, Vector3};
+use stl_io::Triangle;
+
+/// Indexed triangle mesh with shared vertices
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub indices: Vec<[u32; 3]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_vertex(&mut self, position: [f32; 3]) -> u32 {
+        self.vertices.push(position);
+        (self.vertices.len() - 1) as u32
+    }
+
+    pub fn add_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.push([a, b, c]);
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Corner positions of triangle `index`
+    pub fn triangle_points(&self, index: usize) -> [Point3<f32>; 3] {
+        self.indices[index].map(|i| {
+            let [x, y, z] = self.vertices[i as usize];
+            Point3::new(x, y, z)
+        })
+    }
+
+    /// Unit normal of triangle `index` following its winding, or zero if degenerate
+    pub fn face_normal(&self, index: usize) -> Vector3<f32> {
+        let [p1, p2, p3] = self.triangle_points(index);
+        (p2 - p1)
+            .cross(&(p3 - p1))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::zeros)
+    }
+
+    /// Add the vertices and triangles of another mesh, moved by `offset`.
+    /// Per-vertex normals are kept only if both meshes have them.
+    pub fn append(&mut self, other: &Mesh, offset: [f32; 3]) {
+        let base = self.vertices.len() as u32;
+        self.normals = match (self.normals.take(), &other.normals) {
+            (Some(mut normals), Some(other_normals)) if normals.len() == self.vertices.len() => {
+                normals.extend_from_slice(other_normals);
+                Some(normals)
+            }
+            (None, Some(other_normals)) if self.vertices.is_empty() => Some(other_normals.clone()),
+            _ => None,
+        };
+        self.vertices.extend(other.vertices.iter().map(|v| [v[0] + offset[0], v[1] + offset[1], v[2] + offset[2]]));
+        self.indices.extend(other.indices.iter().map(|t| t.map(|i| i + base)));
+    }
+
+    /// Expand into STL triangles with per-face normals
+    pub fn to_stl_triangles(&self) -> Vec<Triangle> {
+        (0..self.indices.len())
+            .map(|index| {
+                let normal = self.face_normal(index);
+                let [p1, p2, p3] = self.triangle_points(index);
+                Triangle {
+                    normal: stl_io::Vector::new([normal.x, normal.y, normal.z]),
+                    vertices: [
+                        stl_io::Vector::new([p1.x, p1.y, p1.z]),
+                        stl_io::Vector::new([p2.x, p2.y, p2.z]),
+                        stl_io::Vector::new([p3.x, p3.y, p3.z]),
+                    ],
+                }
+            })
+            .collect()
+    }
+}
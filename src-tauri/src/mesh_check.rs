@@ -0,0 +1,393 @@
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::mesh::Mesh;
+
+/// Triangles with less area than this (mm²) are reported as degenerate
+const DEGENERATE_AREA: f64 = 1e-9;
+
+/// Topology and geometry findings for a generated mesh
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeshReport {
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    /// Edges used by only one triangle (holes in the surface)
+    pub boundary_edges: usize,
+    /// Edges shared by more than two triangles
+    pub non_manifold_edges: usize,
+    /// Edges whose two triangles traverse them in the same direction
+    pub inconsistent_winding_edges: usize,
+    /// Triangles with zero (or near-zero) area
+    pub degenerate_triangles: usize,
+    /// V - E + F; a closed ring (one hole through it) has 0
+    pub euler_characteristic: i64,
+    /// Pairs of non-adjacent triangles that cross each other
+    pub self_intersections: usize,
+    pub is_watertight: bool,
+    pub is_valid: bool,
+    pub issues: Vec<String>,
+}
+
+/// Check a mesh for holes, non-manifold edges, winding errors, degenerate
+/// triangles and self-intersections
+pub fn check_mesh(mesh: &Mesh) -> MeshReport {
+    // Compare by position rather than index so duplicated vertices don't hide holes
+    let mut lookup: HashMap<[u32; 3], u32> = HashMap::new();
+    let welded: Vec<u32> = mesh
+        .vertices
+        .iter()
+        .map(|v| {
+            let next = lookup.len() as u32;
+            *lookup.entry(v.map(|c| (c + 0.0).to_bits())).or_insert(next)
+        })
+        .collect();
+    let faces: Vec<[u32; 3]> = mesh
+        .indices
+        .iter()
+        .map(|f| f.map(|i| welded[i as usize]))
+        .collect();
+
+    let mut report = MeshReport {
+        vertex_count: mesh.vertex_count(),
+        triangle_count: mesh.triangle_count(),
+        ..Default::default()
+    };
+
+    // Undirected edge -> (uses in min->max direction, uses in max->min direction)
+    let mut edges: HashMap<(u32, u32), (usize, usize)> = HashMap::new();
+    let mut used_vertices = HashSet::new();
+    for face in &faces {
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            used_vertices.insert(a);
+            if a == b {
+                continue;
+            }
+            let entry = edges.entry((a.min(b), a.max(b))).or_default();
+            if a < b {
+                entry.0 += 1;
+            } else {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    for &(forward, backward) in edges.values() {
+        match forward + backward {
+            1 => report.boundary_edges += 1,
+            2 if forward != 1 => report.inconsistent_winding_edges += 1,
+            2 => {}
+            _ => report.non_manifold_edges += 1,
+        }
+    }
+
+    report.degenerate_triangles = (0..faces.len())
+        .filter(|&i| triangle_area(&triangle_points(mesh, i)) < DEGENERATE_AREA)
+        .count();
+
+    report.euler_characteristic =
+        used_vertices.len() as i64 - edges.len() as i64 + faces.len() as i64;
+
+    report.self_intersections = count_self_intersections(mesh, &faces);
+
+    report.is_watertight = report.boundary_edges == 0 && report.non_manifold_edges == 0;
+    report.is_valid = report.is_watertight
+        && report.inconsistent_winding_edges == 0
+        && report.degenerate_triangles == 0
+        && report.self_intersections == 0;
+
+    if report.boundary_edges > 0 {
+        report.issues.push(format!("{} boundary edges (mesh is not closed)", report.boundary_edges));
+    }
+    if report.non_manifold_edges > 0 {
+        report.issues.push(format!("{} non-manifold edges", report.non_manifold_edges));
+    }
+    if report.inconsistent_winding_edges > 0 {
+        report.issues.push(format!("{} edges with inconsistent winding", report.inconsistent_winding_edges));
+    }
+    if report.degenerate_triangles > 0 {
+        report.issues.push(format!("{} degenerate triangles", report.degenerate_triangles));
+    }
+    if report.self_intersections > 0 {
+        report.issues.push(format!("{} self-intersecting triangle pairs", report.self_intersections));
+    }
+    if report.is_watertight && report.euler_characteristic != 0 {
+        report.issues.push(format!(
+            "Euler characteristic is {} (expected 0 for a single ring)",
+            report.euler_characteristic
+        ));
+    }
+
+    report
+}
+
+fn triangle_points(mesh: &Mesh, index: usize) -> [Point3<f64>; 3] {
+    mesh.indices[index].map(|i| {
+        let [x, y, z] = mesh.vertices[i as usize];
+        Point3::new(x as f64, y as f64, z as f64)
+    })
+}
+
+fn triangle_area(points: &[Point3<f64>; 3]) -> f64 {
+    (points[1] - points[0]).cross(&(points[2] - points[0])).norm() * 0.5
+}
+
+/// Count crossing pairs of triangles that share no vertex, using a uniform grid
+/// to avoid testing every pair
+fn count_self_intersections(mesh: &Mesh, faces: &[[u32; 3]]) -> usize {
+    if faces.is_empty() {
+        return 0;
+    }
+
+    let triangles: Vec<[Point3<f64>; 3]> = (0..faces.len()).map(|i| triangle_points(mesh, i)).collect();
+    let bounds: Vec<(Point3<f64>, Point3<f64>)> = triangles
+        .iter()
+        .map(|t| {
+            let min = t[0].inf(&t[1]).inf(&t[2]);
+            let max = t[0].sup(&t[1]).sup(&t[2]);
+            (min, max)
+        })
+        .collect();
+
+    // Cell size of about twice the typical triangle extent; the median keeps a
+    // few tall wall triangles from making every cell huge
+    let mut extents: Vec<f64> = bounds.iter().map(|(min, max)| (max - min).max()).collect();
+    extents.sort_by(|a, b| a.total_cmp(b));
+    let cell_size = (extents[extents.len() / 2] * 2.0).max(1e-3);
+    let cell = |v: f64| (v / cell_size).floor() as i64;
+
+    let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    for (index, (min, max)) in bounds.iter().enumerate() {
+        for x in cell(min.x)..=cell(max.x) {
+            for y in cell(min.y)..=cell(max.y) {
+                for z in cell(min.z)..=cell(max.z) {
+                    grid.entry((x, y, z)).or_default().push(index);
+                }
+            }
+        }
+    }
+
+    let mut tested = HashSet::new();
+    let mut count = 0;
+    for members in grid.values() {
+        for (k, &a) in members.iter().enumerate() {
+            for &b in &members[k + 1..] {
+                let pair = (a.min(b), a.max(b));
+                if faces[a].iter().any(|v| faces[b].contains(v)) || !tested.insert(pair) {
+                    continue;
+                }
+                let (min_a, max_a) = bounds[a];
+                let (min_b, max_b) = bounds[b];
+                let overlap = (0..3).all(|i| min_a[i] <= max_b[i] && min_b[i] <= max_a[i]);
+                if overlap && triangles_intersect(&triangles[a], &triangles[b]) {
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    count
+}
+
+/// Two triangles cross when an edge of either one pierces the other. Edges
+/// lying in the other triangle's plane never pierce it, so triangles in one
+/// plane are compared as overlapping flat shapes instead.
+fn triangles_intersect(a: &[Point3<f64>; 3], b: &[Point3<f64>; 3]) -> bool {
+    if coplanar(a, b) {
+        return coplanar_triangles_overlap(a, b);
+    }
+    (0..3).any(|k| segment_hits_triangle(&a[k], &a[(k + 1) % 3], b))
+        || (0..3).any(|k| segment_hits_triangle(&b[k], &b[(k + 1) % 3], a))
+}
+
+/// Möller–Trumbore segment test, ignoring grazing contacts at the ends
+fn segment_hits_triangle(start: &Point3<f64>, end: &Point3<f64>, triangle: &[Point3<f64>; 3]) -> bool {
+    const EPS: f64 = 1e-9;
+    let direction: Vector3<f64> = end - start;
+    let edge1 = triangle[1] - triangle[0];
+    let edge2 = triangle[2] - triangle[0];
+    let h = direction.cross(&edge2);
+    let det = edge1.dot(&h);
+    if det.abs() < EPS {
+        return false;
+    }
+
+    let s = start - triangle[0];
+    let u = s.dot(&h) / det;
+    if u <= EPS || u >= 1.0 - EPS {
+        return false;
+    }
+    let q = s.cross(&edge1);
+    let v = direction.dot(&q) / det;
+    if v <= EPS || u + v >= 1.0 - EPS {
+        return false;
+    }
+    let t = edge2.dot(&q) / det;
+    t > EPS && t < 1.0 - EPS
+}
+
+fn coplanar(a: &[Point3<f64>; 3], b: &[Point3<f64>; 3]) -> bool {
+    const PLANE_DISTANCE: f64 = 1e-6;
+    let normal = (a[1] - a[0]).cross(&(a[2] - a[0]));
+    let length = normal.norm();
+    length > 0.0 && b.iter().all(|p| ((p - a[0]).dot(&normal) / length).abs() < PLANE_DISTANCE)
+}
+
+/// Overlap of two triangles in one plane: an edge of one properly crosses an
+/// edge of the other, or a corner of one lies inside the other. Triangles that
+/// only touch along an edge or at a corner don't overlap.
+fn coplanar_triangles_overlap(a: &[Point3<f64>; 3], b: &[Point3<f64>; 3]) -> bool {
+    // Drop the coordinate the plane is steepest along
+    let normal = (a[1] - a[0]).cross(&(a[2] - a[0])).abs();
+    let (u, v) = if normal.x >= normal.y && normal.x >= normal.z {
+        (1, 2)
+    } else if normal.y >= normal.z {
+        (0, 2)
+    } else {
+        (0, 1)
+    };
+    let a = a.map(|p| [p[u], p[v]]);
+    let b = b.map(|p| [p[u], p[v]]);
+
+    let edges_cross = (0..3).any(|i| {
+        (0..3).any(|j| segments_cross(&a[i], &a[(i + 1) % 3], &b[j], &b[(j + 1) % 3]))
+    });
+    edges_cross
+        || b.iter().any(|p| point_inside_triangle(p, &a))
+        || a.iter().any(|p| point_inside_triangle(p, &b))
+}
+
+/// Twice the signed area of p, q, r; positive when they turn counterclockwise
+fn orientation(p: &[f64; 2], q: &[f64; 2], r: &[f64; 2]) -> f64 {
+    (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])
+}
+
+/// Segments p1-p2 and q1-q2 cross at a point inside both
+fn segments_cross(p1: &[f64; 2], p2: &[f64; 2], q1: &[f64; 2], q2: &[f64; 2]) -> bool {
+    const EPS: f64 = 1e-9;
+    let opposite = |s: f64, t: f64| (s > EPS && t < -EPS) || (s < -EPS && t > EPS);
+    opposite(orientation(p1, p2, q1), orientation(p1, p2, q2))
+        && opposite(orientation(q1, q2, p1), orientation(q1, q2, p2))
+}
+
+fn point_inside_triangle(p: &[f64; 2], triangle: &[[f64; 2]; 3]) -> bool {
+    const EPS: f64 = 1e-9;
+    let sides = (0..3).map(|k| orientation(&triangle[k], &triangle[(k + 1) % 3], p));
+    let (mut positive, mut negative) = (0, 0);
+    for side in sides {
+        if side > EPS {
+            positive += 1;
+        } else if side < -EPS {
+            negative += 1;
+        }
+    }
+    positive == 3 || negative == 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mesh(vertices: &[[f32; 3]], triangles: &[[u32; 3]]) -> Mesh {
+        let mut mesh = Mesh::new();
+        for &vertex in vertices {
+            mesh.add_vertex(vertex);
+        }
+        for &[a, b, c] in triangles {
+            mesh.add_triangle(a, b, c);
+        }
+        mesh
+    }
+
+    const TETRAHEDRON_VERTICES: [[f32; 3]; 4] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    const TETRAHEDRON_FACES: [[u32; 3]; 4] = [[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]];
+
+    #[test]
+    fn closed_tetrahedron_is_valid() {
+        let report = check_mesh(&mesh(&TETRAHEDRON_VERTICES, &TETRAHEDRON_FACES));
+        assert!(report.is_watertight && report.is_valid, "{:?}", report.issues);
+        // A closed surface without a hole through it
+        assert_eq!(report.euler_characteristic, 2);
+    }
+
+    #[test]
+    fn open_surface_has_boundary_edges() {
+        let report = check_mesh(&mesh(&TETRAHEDRON_VERTICES, &TETRAHEDRON_FACES[..3]));
+        assert_eq!(report.boundary_edges, 3);
+        assert!(!report.is_watertight && !report.is_valid);
+    }
+
+    #[test]
+    fn duplicated_vertices_do_not_hide_holes() {
+        let mut vertices = TETRAHEDRON_VERTICES.to_vec();
+        vertices.push(TETRAHEDRON_VERTICES[0]);
+        let report = check_mesh(&mesh(&vertices, &[[0, 2, 1], [0, 1, 3], [1, 2, 3], [4, 3, 2]]));
+        assert!(report.is_valid, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn edge_shared_by_three_triangles_is_non_manifold() {
+        let mut vertices = TETRAHEDRON_VERTICES.to_vec();
+        vertices.push([1.0, 1.0, -1.0]);
+        let mut faces = TETRAHEDRON_FACES.to_vec();
+        faces.push([0, 1, 4]);
+        let report = check_mesh(&mesh(&vertices, &faces));
+        assert_eq!(report.non_manifold_edges, 1);
+        assert_eq!(report.boundary_edges, 2);
+        assert!(!report.is_watertight);
+    }
+
+    #[test]
+    fn flipped_triangle_has_inconsistent_winding() {
+        let mut faces = TETRAHEDRON_FACES;
+        faces[2] = [1, 3, 2];
+        let report = check_mesh(&mesh(&TETRAHEDRON_VERTICES, &faces));
+        assert_eq!(report.inconsistent_winding_edges, 3);
+        assert!(report.is_watertight && !report.is_valid);
+    }
+
+    #[test]
+    fn collinear_triangle_is_degenerate() {
+        let report = check_mesh(&mesh(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]], &[[0, 1, 2]]));
+        assert_eq!(report.degenerate_triangles, 1);
+    }
+
+    #[test]
+    fn crossing_triangles_intersect() {
+        let vertices = [
+            [0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0],
+            [0.5, 0.5, -1.0], [0.5, 0.5, 1.0], [3.0, 3.0, 0.0],
+        ];
+        let report = check_mesh(&mesh(&vertices, &[[0, 1, 2], [3, 4, 5]]));
+        assert_eq!(report.self_intersections, 1);
+    }
+
+    #[test]
+    fn overlapping_coplanar_triangles_intersect() {
+        let vertices = [
+            [0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0],
+            [0.5, 0.5, 0.0], [3.0, 0.5, 0.0], [0.5, 3.0, 0.0],
+        ];
+        let report = check_mesh(&mesh(&vertices, &[[0, 1, 2], [3, 4, 5]]));
+        assert_eq!(report.self_intersections, 1);
+
+        // One triangle entirely inside the other
+        let vertices = [
+            [0.0, 0.0, 0.0], [4.0, 0.0, 0.0], [0.0, 4.0, 0.0],
+            [0.5, 0.5, 0.0], [1.0, 0.5, 0.0], [0.5, 1.0, 0.0],
+        ];
+        let report = check_mesh(&mesh(&vertices, &[[0, 1, 2], [3, 4, 5]]));
+        assert_eq!(report.self_intersections, 1);
+    }
+
+    #[test]
+    fn separate_coplanar_triangles_do_not_intersect() {
+        let vertices = [
+            [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0], [2.0, 1.0, 0.0], [1.0, 2.0, 0.0],
+        ];
+        let report = check_mesh(&mesh(&vertices, &[[0, 1, 2], [3, 4, 5]]));
+        assert_eq!(report.self_intersections, 0);
+    }
+}
@@ -0,0 +1,96 @@
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::Mesh;
+
+/// Physical properties of a closed mesh, in millimetres
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeshStats {
+    /// Enclosed volume (mm³); negative when the normals point inward
+    pub volume: f64,
+    /// Total triangle area (mm²)
+    pub surface_area: f64,
+    /// Center of mass of the enclosed solid, assuming uniform density
+    pub centroid: [f64; 3],
+    pub bounding_box_min: [f32; 3],
+    pub bounding_box_max: [f32; 3],
+}
+
+/// Compute volume, surface area, centroid and bounding box of a mesh.
+/// Volume and centroid are only meaningful for a closed mesh.
+pub fn compute_mesh_stats(mesh: &Mesh) -> MeshStats {
+    let mut stats = MeshStats::default();
+    if mesh.vertices.is_empty() {
+        return stats;
+    }
+
+    // Sum signed tetrahedra from the origin to each triangle
+    let mut moment = Vector3::<f64>::zeros();
+    for face in &mesh.indices {
+        let [a, b, c] = face.map(|i| {
+            let [x, y, z] = mesh.vertices[i as usize];
+            Vector3::new(x as f64, y as f64, z as f64)
+        });
+        let cross = (b - a).cross(&(c - a));
+        stats.surface_area += cross.norm() * 0.5;
+
+        let volume = a.dot(&b.cross(&c)) / 6.0;
+        stats.volume += volume;
+        moment += (a + b + c) * (volume / 4.0);
+    }
+    if stats.volume.abs() > f64::EPSILON {
+        let centroid = moment / stats.volume;
+        stats.centroid = [centroid.x, centroid.y, centroid.z];
+    }
+
+    stats.bounding_box_min = mesh.vertices.iter().fold([f32::MAX; 3], |min, v| [0, 1, 2].map(|i| min[i].min(v[i])));
+    stats.bounding_box_max = mesh.vertices.iter().fold([f32::MIN; 3], |max, v| [0, 1, 2].map(|i| max[i].max(v[i])));
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{revolve_profile, RingProfile};
+
+    #[test]
+    fn rectangular_ring_matches_the_hollow_cylinder() {
+        let (outer, inner, height) = (30.0f32, 20.0f32, 8.0f32);
+        let mut profile = RingProfile::new(inner, 0.0);
+        profile.line_to(outer, 0.0);
+        profile.line_to(outer, height);
+        profile.line_to(inner, height);
+        profile.close();
+
+        let segments = 256;
+        let stats = compute_mesh_stats(&revolve_profile(&profile, segments));
+
+        // The polygon is inscribed in the circles, so it falls short by about (2π/n)²/6
+        let exact = std::f64::consts::PI * (outer.powi(2) - inner.powi(2)) as f64 * height as f64;
+        let tolerance = (2.0 * std::f64::consts::PI / segments as f64).powi(2) / 6.0 * 1.1;
+        assert!(stats.volume < exact, "volume {} exceeds the true ring {}", stats.volume, exact);
+        assert!(
+            (exact - stats.volume) / exact < tolerance,
+            "volume {} too far from {}", stats.volume, exact
+        );
+
+        let pi = std::f64::consts::PI;
+        let (outer, inner, height) = (outer as f64, inner as f64, height as f64);
+        let area = 2.0 * pi * (outer + inner) * height + 2.0 * pi * (outer.powi(2) - inner.powi(2));
+        assert!((area - stats.surface_area) / area < tolerance, "area {} too far from {}", stats.surface_area, area);
+
+        assert!((stats.centroid[2] - height / 2.0).abs() < 1e-4, "centroid {:?}", stats.centroid);
+        assert!(stats.centroid[0].abs() < 1e-4 && stats.centroid[1].abs() < 1e-4, "centroid {:?}", stats.centroid);
+        assert_eq!(stats.bounding_box_min[2], 0.0);
+        assert_eq!(stats.bounding_box_max[2] as f64, height);
+        assert!((stats.bounding_box_max[0] as f64 - outer).abs() < 1e-4);
+    }
+
+    #[test]
+    fn empty_mesh_has_no_stats() {
+        let stats = compute_mesh_stats(&Mesh::new());
+        assert_eq!(stats.volume, 0.0);
+        assert_eq!(stats.surface_area, 0.0);
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+
+use crate::geometry::{arc_sweep, ring_profile, ProfilePoint, ProfileSegment, RingParameters};
+
+/// Space around the profile in the SVG view, and how far the axis line overhangs it (mm)
+const MARGIN: f32 = 2.0;
+/// Segments shorter than this are left out (mm)
+const MIN_EDGE_LENGTH: f32 = 1e-6;
+
+/// Cross-section of the ring as an ASCII DXF: the profile as lines and arcs
+/// on layer PROFILE, the axis of revolution on layer AXIS, with X = radius
+/// and Y = height in millimetres (stated in a leading comment, as R12 has no
+/// units header variable)
+pub fn dxf_document(params: &RingParameters) -> Result<String> {
+    let profile = ring_profile(params)?;
+    let mut dxf = String::new();
+
+    // R12 has no units header variable, so the units are stated in a comment
+    dxf.push_str("999\nUnits: millimetres\n");
+    dxf.push_str("0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1009\n0\nENDSEC\n");
+    dxf.push_str("0\nSECTION\n2\nENTITIES\n");
+
+    let _ = write!(
+        dxf,
+        "0\nLINE\n8\nAXIS\n10\n0.0\n20\n{}\n30\n0.0\n11\n0.0\n21\n{}\n31\n0.0\n",
+        -MARGIN,
+        params.height + MARGIN
+    );
+    for (start, segment) in profile.edges(MIN_EDGE_LENGTH) {
+        match segment {
+            ProfileSegment::Line { end } => {
+                let _ = write!(
+                    dxf,
+                    "0\nLINE\n8\nPROFILE\n10\n{}\n20\n{}\n30\n0.0\n11\n{}\n21\n{}\n31\n0.0\n",
+                    start.0, start.1, end.0, end.1
+                );
+            }
+            ProfileSegment::Arc { end, center, ccw } => {
+                // DXF arcs always run counter-clockwise from the start angle to the end angle
+                let (from, to) = if ccw { (start, end) } else { (end, start) };
+                let radius = distance(center, from);
+                let _ = write!(
+                    dxf,
+                    "0\nARC\n8\nPROFILE\n10\n{}\n20\n{}\n30\n0.0\n40\n{}\n50\n{}\n51\n{}\n",
+                    center.0,
+                    center.1,
+                    radius,
+                    angle_deg(center, from),
+                    angle_deg(center, to)
+                );
+            }
+        }
+    }
+
+    dxf.push_str("0\nENDSEC\n0\nEOF\n");
+    Ok(dxf)
+}
+
+/// Cross-section of the ring as an SVG at 1:1 scale in millimetres, with the
+/// axis of revolution as a dashed line on the left
+pub fn svg_document(params: &RingParameters) -> Result<String> {
+    let profile = ring_profile(params)?;
+    let (min_x, max_x) = (-MARGIN, params.outer_diameter / 2.0 + MARGIN);
+    let (min_z, max_z) = (-MARGIN, params.height + MARGIN);
+    let (width, height) = (max_x - min_x, max_z - min_z);
+
+    let mut path = format!("M {} {}", profile.start.0, profile.start.1);
+    for (start, segment) in profile.edges(MIN_EDGE_LENGTH) {
+        match segment {
+            ProfileSegment::Line { end } => {
+                let _ = write!(path, " L {} {}", end.0, end.1);
+            }
+            ProfileSegment::Arc { end, center, ccw } => {
+                let radius = distance(center, start);
+                let large_arc = arc_sweep(start, end, center, ccw).abs() > std::f32::consts::PI;
+                // Flags are read in the flipped (z up) coordinates of the group
+                let _ = write!(
+                    path,
+                    " A {} {} 0 {} {} {} {}",
+                    radius, radius, large_arc as u8, ccw as u8, end.0, end.1
+                );
+            }
+        }
+    }
+    path.push_str(" Z");
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"{x} {y} {w} {h}\">",
+        w = width,
+        h = height,
+        x = min_x,
+        y = -max_z
+    );
+    let _ = writeln!(svg, "  <title>{} cross-section</title>", params.file_stem());
+    // Flip Y so the profile reads with z pointing up
+    svg.push_str("  <g transform=\"scale(1,-1)\" fill=\"none\" stroke-width=\"0.1\">\n");
+    let _ = writeln!(
+        svg,
+        "    <line x1=\"0\" y1=\"{}\" x2=\"0\" y2=\"{}\" stroke=\"#888\" stroke-dasharray=\"2 0.5 0.5 0.5\"/>",
+        min_z, max_z
+    );
+    let _ = writeln!(svg, "    <path d=\"{}\" stroke=\"#000\" fill=\"#ddd\"/>", path);
+    svg.push_str("  </g>\n</svg>\n");
+
+    Ok(svg)
+}
+
+fn distance(a: ProfilePoint, b: ProfilePoint) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Direction from `center` to `point` in degrees, counter-clockwise from +X
+fn angle_deg(center: ProfilePoint, point: ProfilePoint) -> f32 {
+    (point.1 - center.1).atan2(point.0 - center.0).to_degrees().rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{LensSeat, LensSurface, RingType, SeatType};
+
+    fn seated_ring() -> RingParameters {
+        let mut params = RingParameters::with_defaults(RingType::Concave, 50.0, 40.0);
+        params.lens_seat = Some(LensSeat {
+            radius_of_curvature: 60.0,
+            surface: LensSurface::Concave,
+            seat_type: SeatType::Spherical,
+            contact_diameter: Some(46.0),
+        });
+        params.validate().unwrap();
+        params
+    }
+
+    /// Group code and value pairs of a DXF file
+    fn groups(dxf: &str) -> Vec<(i32, &str)> {
+        let lines: Vec<&str> = dxf.lines().collect();
+        lines.chunks(2).map(|pair| (pair[0].trim().parse().unwrap(), pair[1])).collect()
+    }
+
+    /// Entities as their type and group values, in file order
+    fn entities(dxf: &str) -> Vec<(&str, Vec<(i32, f32)>)> {
+        let groups = groups(dxf);
+        let start = groups.iter().position(|&g| g == (2, "ENTITIES")).unwrap() + 1;
+        let mut entities: Vec<(&str, Vec<(i32, f32)>)> = Vec::new();
+        for &(code, value) in &groups[start..] {
+            match code {
+                0 if value == "ENDSEC" => break,
+                0 => entities.push((value, Vec::new())),
+                8 => {}
+                _ => entities.last_mut().unwrap().1.push((code, value.parse().unwrap())),
+            }
+        }
+        entities
+    }
+
+    fn value(groups: &[(i32, f32)], code: i32) -> f32 {
+        groups.iter().find(|g| g.0 == code).unwrap().1
+    }
+
+    #[test]
+    fn dxf_is_an_r12_file_without_newer_header_variables() {
+        let dxf = dxf_document(&seated_ring()).unwrap();
+        let groups = groups(&dxf);
+        assert_eq!(groups[0], (999, "Units: millimetres"));
+        let header: Vec<&str> = groups.iter().filter(|g| g.0 == 9).map(|g| g.1).collect();
+        assert_eq!(header, ["$ACADVER"]);
+        assert!(groups.contains(&(1, "AC1009")));
+        assert_eq!(groups.last(), Some(&(0, "EOF")));
+    }
+
+    #[test]
+    fn dxf_has_the_seat_arcs_running_counter_clockwise() {
+        let params = seated_ring();
+        let dxf = dxf_document(&params).unwrap();
+        let entities = entities(&dxf);
+        // Axis, base, outer wall and bore as lines; the seat on both sides of the contact as arcs
+        assert_eq!(entities.iter().filter(|e| e.0 == "LINE").count(), 4);
+        assert_eq!(entities.iter().filter(|e| e.0 == "ARC").count(), 2);
+
+        let edges = ring_profile(&params).unwrap().edges(MIN_EDGE_LENGTH);
+        let arcs = edges.into_iter().filter_map(|(start, segment)| match segment {
+            ProfileSegment::Arc { end, center, ccw } => Some(arc_sweep(start, end, center, ccw).abs().to_degrees()),
+            _ => None,
+        });
+        for (sweep, (_, groups)) in arcs.zip(entities.iter().filter(|e| e.0 == "ARC")) {
+            let dxf_sweep = (value(groups, 51) - value(groups, 50)).rem_euclid(360.0);
+            assert!((dxf_sweep - sweep).abs() < 1e-3, "arc sweeps {}° instead of {}°", dxf_sweep, sweep);
+            assert!((value(groups, 40) - 60.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn svg_path_is_closed() {
+        let params = seated_ring();
+        let svg = svg_document(&params).unwrap();
+        let path = svg.split("<path d=\"").nth(1).unwrap().split('"').next().unwrap();
+        assert!(path.starts_with("M ") && path.ends_with(" Z"), "{}", path);
+        assert_eq!(path.matches(" A ").count(), 2);
+
+        // The last segment ends where the path started
+        let numbers = |text: &str| -> Vec<f32> { text.split(' ').filter_map(|n| n.parse().ok()).collect() };
+        let start = numbers(&path[2..]);
+        let last = numbers(path.rsplit(['L', 'A']).next().unwrap());
+        assert!((start[0] - last[last.len() - 2]).abs() < 1e-4 && (start[1] - last[last.len() - 1]).abs() < 1e-4);
+    }
+}
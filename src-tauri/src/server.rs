@@ -0,0 +1,414 @@
+// Local HTTP API for lab automation: the generator without a window, on
+// std::net alone. One request per connection, handled by a fixed pool of
+// worker threads.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::geometry::{generate_ring_mesh, RingParameters, RingType};
+use crate::manufacturing::analyze_for_printing;
+use crate::mesh::Mesh;
+use crate::mesh_check::check_mesh;
+use crate::mesh_stats::compute_mesh_stats;
+use crate::stl_output::OutputFormat;
+use crate::RingRequest;
+
+/// Port `serve` listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 8471;
+
+/// Largest request body accepted; a ring request is a few hundred bytes
+const MAX_BODY: usize = 64 * 1024;
+/// Longest request or header line accepted
+const MAX_LINE: usize = 8 * 1024;
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections waiting for a free worker; further ones are turned away as busy
+const QUEUE_LENGTH: usize = 16;
+
+/// Method and path of every endpoint
+const ENDPOINTS: [(&str, &str); 4] = [
+    ("GET", "/ring-types"),
+    ("POST", "/generate"),
+    ("POST", "/preview-stats"),
+    ("POST", "/validate"),
+];
+
+struct Request {
+    method: String,
+    /// Path without the query string, which no endpoint uses
+    path: String,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(status: u16, value: &Value) -> Self {
+        let mut body = serde_json::to_vec_pretty(value).unwrap_or_default();
+        body.push(b'\n');
+        Response { status, content_type: "application/json", headers: Vec::new(), body }
+    }
+
+    /// Error in the shape the command line tool prints
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Response::json(status, &json!({ "success": false, "message": message.into() }))
+    }
+}
+
+/// Answer requests on the listener until the process ends, on one worker
+/// thread per core
+pub fn serve(listener: TcpListener) -> Result<()> {
+    let address = listener.local_addr()?;
+    eprintln!("Listening on http://{}", address);
+    if !address.ip().is_loopback() {
+        eprintln!(
+            "Warning: {} is reachable from other machines, and the API has no authentication; \
+             anyone who can connect can make this machine generate rings",
+            address.ip()
+        );
+    }
+
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(QUEUE_LENGTH);
+    let receiver = Arc::new(Mutex::new(receiver));
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    for _ in 0..workers {
+        let receiver = Arc::clone(&receiver);
+        std::thread::spawn(move || loop {
+            // The lock is only held while waiting, not while handling the request
+            let stream = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => break,
+            };
+            let Ok(stream) = stream else { break };
+            handle_connection(stream);
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(TrySendError::Full(mut stream) | TrySendError::Disconnected(mut stream)) = sender.try_send(stream) {
+                    let _ = write_response(&mut stream, &Response::error(503, "The server is busy, try again shortly"));
+                }
+            }
+            Err(e) => eprintln!("Failed to accept a connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Segment count, mesh statistics and mesh check of a ring, as `preview-stats` reports them
+pub fn preview_report(params: &RingParameters, mesh: &Mesh) -> Value {
+    json!({
+        "success": true,
+        "ring_type": params.type_code(),
+        "segments": params.segment_count(),
+        "stats": compute_mesh_stats(mesh),
+        "mesh_check": check_mesh(mesh),
+    })
+}
+
+/// Mesh check and printability of a ring, as `validate` reports them
+pub fn validation_report(params: &RingParameters, mesh: &Mesh) -> Value {
+    let report = check_mesh(mesh);
+    let manufacturing = analyze_for_printing(params, mesh, &report);
+    json!({
+        "success": true,
+        "valid": report.is_valid,
+        "printable": manufacturing.printable,
+        "mesh_check": report,
+        "manufacturing": manufacturing,
+    })
+}
+
+fn handle_connection(mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let response = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => {
+            let response = route(&request);
+            eprintln!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+    if let Err(e) = write_response(&mut stream, &response) {
+        eprintln!("Failed to send response: {}", e);
+    }
+}
+
+fn route(request: &Request) -> Response {
+    let body = &request.body;
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/ring-types") => Ok(Response::json(200, &ring_types())),
+        ("POST", "/generate") => generate(body),
+        ("POST", "/preview-stats") => parse_request(body)
+            .and_then(|(_, params, _)| Ok(Response::json(200, &preview_report(&params, &ring_mesh(&params)?)))),
+        ("POST", "/validate") => parse_request(body)
+            .and_then(|(_, params, _)| Ok(Response::json(200, &validation_report(&params, &ring_mesh(&params)?)))),
+        (_, path) => Err(match ENDPOINTS.iter().find(|(_, endpoint)| *endpoint == path) {
+            Some((method, _)) => {
+                let mut response = Response::error(405, format!("Use {} for {}", method, path));
+                response.headers.push(("Allow", method.to_string()));
+                response
+            }
+            None => {
+                let endpoints: Vec<String> = ENDPOINTS.iter().map(|(method, path)| format!("{} {}", method, path)).collect();
+                Response::error(404, format!("No endpoint at {}. Endpoints are: {}", path, endpoints.join(", ")))
+            }
+        }),
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+fn ring_types() -> Value {
+    let ring_types: Vec<Value> = RingType::ALL
+        .iter()
+        .map(|ring_type| json!({ "code": ring_type.to_string(), "description": ring_type.description() }))
+        .collect();
+    json!({ "success": true, "ring_types": ring_types })
+}
+
+/// Parse a JSON ring request completely; anything wrong with it is a client error
+fn parse_request(body: &[u8]) -> Result<(RingRequest, RingParameters, OutputFormat), Response> {
+    let request: RingRequest = serde_json::from_slice(body)
+        .map_err(|e| Response::error(400, format!("Invalid request: {}", e)))?;
+    let params = request.to_parameters().map_err(|e| Response::error(400, e))?;
+    let format = request.output_format().map_err(|e| Response::error(400, e))?;
+    request.drawing_format().map_err(|e| Response::error(400, e))?;
+    Ok((request, params, format))
+}
+
+fn ring_mesh(params: &RingParameters) -> Result<Mesh, Response> {
+    generate_ring_mesh(params).map_err(|e| Response::error(500, format!("Failed to generate mesh: {}", e)))
+}
+
+/// The ring file itself, in the requested format. Nothing is written on the
+/// server, so requests naming an output path or a drawing are refused.
+fn generate(body: &[u8]) -> Result<Response, Response> {
+    let (request, params, format) = parse_request(body)?;
+    if request.output_path().is_some() || request.drawing.is_some() {
+        return Err(Response::error(400, "The ring file is sent in the response; output_path and drawing are not used"));
+    }
+    let mesh = ring_mesh(&params)?;
+    let report = check_mesh(&mesh);
+
+    let mut data = Cursor::new(Vec::new());
+    format.writer().write(&mut data, &mesh, &params)
+        .map_err(|e| Response::error(500, format!("Failed to write {} file: {}", format.extension().to_uppercase(), e)))?;
+
+    Ok(Response {
+        status: 200,
+        content_type: format.mime_type(),
+        headers: vec![
+            ("Content-Disposition", format!("attachment; filename=\"{}\"", params.filename(format.extension()))),
+            ("X-Mesh-Valid", report.is_valid.to_string()),
+        ],
+        body: data.into_inner(),
+    })
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let line = read_line(reader)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(Response::error(400, "Malformed request line"));
+    };
+    let path = target.split('?').next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Response::error(400, format!("Malformed header: {}", line)));
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse()
+                .map_err(|_| Response::error(400, "Invalid Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(Response::error(411, "Send the request body with a Content-Length"));
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(Response::error(413, format!("Request bodies are limited to {} bytes", MAX_BODY)));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)
+        .map_err(|e| Response::error(400, format!("Failed to read the request body: {}", e)))?;
+    Ok(Request { method: method.to_string(), path, body })
+}
+
+/// One line of the request head, without its line ending
+fn read_line(reader: &mut impl BufRead) -> Result<String, Response> {
+    let mut line = Vec::new();
+    reader.by_ref().take(MAX_LINE as u64).read_until(b'\n', &mut line)
+        .map_err(|e| Response::error(400, format!("Failed to read the request: {}", e)))?;
+    if !line.ends_with(b"\n") {
+        return Err(Response::error(400, "Request line too long or incomplete"));
+    }
+    String::from_utf8(line)
+        .map(|line| line.trim_end().to_string())
+        .map_err(|_| Response::error(400, "Request head is not UTF-8"))
+}
+
+fn write_response(stream: &mut impl Write, response: &Response) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        let _ = write!(head, "{}: {}\r\n", name, value);
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Response to a raw HTTP request, as `handle_connection` would send it
+    fn respond(raw: &str) -> Response {
+        match read_request(&mut Cursor::new(raw.as_bytes())) {
+            Ok(request) => route(&request),
+            Err(response) => response,
+        }
+    }
+
+    fn post(path: &str, body: &str) -> Response {
+        respond(&format!("POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body))
+    }
+
+    fn json_body(response: &Response) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    #[test]
+    fn requests_are_parsed_from_the_wire() {
+        let body = r#"{"ring_type": "CX"}"#;
+        let raw = format!("POST /validate?verbose=1 HTTP/1.1\r\ncontent-length: {}\r\nX-Other: a:b\r\n\r\n{}", body.len(), body);
+        let request = read_request(&mut Cursor::new(raw.as_bytes())).unwrap_or_else(|_| panic!("not parsed"));
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/validate"));
+        assert_eq!(request.body, body.as_bytes());
+    }
+
+    #[test]
+    fn malformed_requests_are_client_errors() {
+        let status = |raw: &str| respond(raw).status;
+        assert_eq!(status("GET /ring-types\r\n\r\n"), 400);
+        assert_eq!(status("GET /ring-types HTTP/1.1\r\nNo colon\r\n\r\n"), 400);
+        assert_eq!(status("GET /ring-types HTTP/1.1\r\nContent-Length: many\r\n\r\n"), 400);
+        assert_eq!(status("POST /validate HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"), 411);
+        assert_eq!(status(&format!("POST /validate HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY + 1)), 413);
+        assert_eq!(status(&format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE))), 400);
+        // The body is shorter than announced
+        assert_eq!(status("POST /validate HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}"), 400);
+    }
+
+    #[test]
+    fn unknown_paths_and_methods_are_refused() {
+        let response = respond("GET /nothing HTTP/1.1\r\n\r\n");
+        assert_eq!(response.status, 404);
+        assert!(json_body(&response)["message"].as_str().unwrap().contains("GET /ring-types"));
+
+        let response = post("/ring-types", "");
+        assert_eq!(response.status, 405);
+        assert_eq!(response.headers, [("Allow", "GET".to_string())]);
+    }
+
+    #[test]
+    fn ring_types_lists_every_type() {
+        let response = respond("GET /ring-types HTTP/1.1\r\n\r\n");
+        assert_eq!((response.status, response.content_type), (200, "application/json"));
+        let codes: Vec<Value> = json_body(&response)["ring_types"].as_array().unwrap().iter().map(|t| t["code"].clone()).collect();
+        assert_eq!(codes, ["CX", "CC", "3P"]);
+    }
+
+    #[test]
+    fn validate_reports_the_mesh_check() {
+        let response = post("/validate", r#"{"ring_type": "CX", "outer_diameter": 50, "inner_diameter": 40}"#);
+        assert_eq!(response.status, 200);
+        let report = json_body(&response);
+        assert_eq!(report["valid"], report["mesh_check"]["is_valid"]);
+        assert_eq!(report["valid"], true);
+
+        let response = post("/validate", r#"{"ring_type": "CX", "outer_diameter": 40, "inner_diameter": 50}"#);
+        assert_eq!(response.status, 400);
+        assert!(json_body(&response)["message"].as_str().unwrap().starts_with("Invalid parameters:"));
+        assert_eq!(post("/preview-stats", "{").status, 400);
+    }
+
+    #[test]
+    fn generate_sends_the_ring_file() {
+        let response = post("/generate", r#"{"ring_type": "CC", "outer_diameter": 50, "inner_diameter": 40, "format": "obj"}"#);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, OutputFormat::Obj.mime_type());
+        assert!(response.headers.contains(&("Content-Disposition", "attachment; filename=\"CC-40.0.obj\"".to_string())));
+        assert!(response.headers.contains(&("X-Mesh-Valid", "true".to_string())));
+        assert!(response.body.starts_with(b"# Optics Ring Generator"));
+
+        let response = post("/generate", r#"{"ring_type": "CC", "outer_diameter": 50, "inner_diameter": 40, "output_path": "/tmp"}"#);
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn responses_are_written_with_their_headers() {
+        let mut response = Response::error(503, "busy");
+        response.headers.push(("Retry-After", "1".to_string()));
+        let mut data = Vec::new();
+        write_response(&mut data, &response).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        let (head, body) = text.split_once("\r\n\r\n").unwrap();
+        assert_eq!(head, format!(
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\nRetry-After: 1",
+            body.len()
+        ));
+    }
+
+    #[test]
+    fn serve_answers_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(listener));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET /ring-types HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut text = String::new();
+        stream.read_to_string(&mut text).unwrap();
+        assert!(text.starts_with("HTTP/1.1 200 OK\r\n"), "{}", text);
+        assert!(text.contains("\"code\": \"3P\""), "{}", text);
+    }
+}
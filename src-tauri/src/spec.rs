@@ -0,0 +1,308 @@
+// Ring spec files (`.ring.toml`, or JSON): every parameter of a ring with its
+// export settings and metadata, versioned so old specs keep loading
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::drawing::DrawingFormat;
+use crate::geometry::RingParameters;
+use crate::stl_output::{write_complete_file, OutputFormat};
+use crate::timestamp::utc_timestamp;
+use crate::RingRequest;
+
+/// Version of the spec documents this build writes
+pub const SPEC_VERSION: u32 = 1;
+
+/// File name ending of TOML ring specs
+pub const SPEC_EXTENSION: &str = ".ring.toml";
+
+/// Upgrades of a spec document by one version; entry `n` turns version `n`
+/// into version `n + 1`
+const MIGRATIONS: [fn(Value) -> Result<Value>; SPEC_VERSION as usize] = [migrate_v0];
+
+/// A ring described completely enough to regenerate the same files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RingSpec {
+    pub version: u32,
+    #[serde(default)]
+    pub metadata: SpecMetadata,
+    pub ring: RingParameters,
+    #[serde(default)]
+    pub export: ExportSettings,
+}
+
+/// Free text about the ring; none of it changes the generated files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// When the spec was created, as a UTC timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Application and version that last saved the spec
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<String>,
+}
+
+/// Files written for the ring
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportSettings {
+    #[serde(default)]
+    pub format: OutputFormat,
+    /// Dimensioned drawing saved next to the ring file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drawing: Option<DrawingFormat>,
+}
+
+/// Text format of a spec file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecFormat {
+    Toml,
+    Json,
+}
+
+impl SpecFormat {
+    /// JSON for `.json` files, TOML for anything else
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => SpecFormat::Json,
+            _ => SpecFormat::Toml,
+        }
+    }
+}
+
+impl RingSpec {
+    /// Spec of a validated ring, created now
+    pub fn new(ring: RingParameters, export: ExportSettings) -> Self {
+        RingSpec {
+            version: SPEC_VERSION,
+            metadata: SpecMetadata { created: Some(utc_timestamp()), ..SpecMetadata::default() },
+            ring,
+            export,
+        }
+    }
+
+    /// Spec of the ring and files of a request; where they are written is not part of it
+    pub fn from_request(request: &RingRequest) -> Result<Self> {
+        let ring = request.to_parameters().map_err(|e| anyhow!(e))?;
+        let format = request.output_format().map_err(|e| anyhow!(e))?;
+        let drawing = request.drawing_format().map_err(|e| anyhow!(e))?;
+        Ok(RingSpec::new(ring, ExportSettings { format, drawing }))
+    }
+
+    /// Request that regenerates the spec's files
+    pub fn to_request(&self) -> RingRequest {
+        let mut request = RingRequest::from(&self.ring);
+        request.format = Some(self.export.format.to_string());
+        request.drawing = self.export.drawing.map(|drawing| drawing.to_string());
+        request
+    }
+
+    /// Read a spec of any version up to `SPEC_VERSION`, migrating older ones
+    pub fn parse(text: &str, format: SpecFormat) -> Result<Self> {
+        let mut document = parse_document(text, format)?;
+        let version = document_version(&document)?;
+        if version > SPEC_VERSION {
+            bail!(
+                "Spec version {} is newer than this application supports (up to {})",
+                version, SPEC_VERSION
+            );
+        }
+        for migrate in &MIGRATIONS[version as usize..] {
+            document = migrate(document)?;
+        }
+
+        let spec: RingSpec = serde_json::from_value(document)
+            .map_err(|e| anyhow!("Invalid ring spec: {}", e))?;
+        spec.ring.validate()
+            .map_err(|e| anyhow!("Invalid parameters: {}", e))?;
+        Ok(spec)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        RingSpec::parse(&text, SpecFormat::for_path(path))
+            .map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))
+    }
+
+    /// The spec as a current version document, stamped with this application
+    pub fn to_text(&self, format: SpecFormat) -> Result<String> {
+        let mut spec = self.clone();
+        spec.version = SPEC_VERSION;
+        spec.metadata.generator = Some(format!("Optics Ring Generator {}", env!("CARGO_PKG_VERSION")));
+
+        // serde_json prints an f32 in its shortest form, where the TOML writer
+        // would print 0.05 as 0.05000000074505806
+        let json = serde_json::to_string_pretty(&spec)?;
+        match format {
+            SpecFormat::Json => Ok(json + "\n"),
+            SpecFormat::Toml => {
+                let document: toml::Value = serde_json::from_str(&json)?;
+                Ok(toml::to_string_pretty(&document)?)
+            }
+        }
+    }
+
+    /// Write the spec in the format its file name asks for
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = self.to_text(SpecFormat::for_path(path))?;
+        write_complete_file(path, text.as_bytes())
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Version a spec document was written in
+pub fn spec_version(text: &str, format: SpecFormat) -> Result<u32> {
+    document_version(&parse_document(text, format)?)
+}
+
+fn parse_document(text: &str, format: SpecFormat) -> Result<Value> {
+    let document: Value = match format {
+        SpecFormat::Toml => toml::from_str(text).map_err(|e| anyhow!("Invalid TOML: {}", e))?,
+        SpecFormat::Json => serde_json::from_str(text).map_err(|e| anyhow!("Invalid JSON: {}", e))?,
+    };
+    if !document.is_object() {
+        bail!("A ring spec must be a table of fields");
+    }
+    Ok(document)
+}
+
+/// Documents without a version are version 0
+fn document_version(document: &Value) -> Result<u32> {
+    match document.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow!("Spec version must be a whole number, not {}", version)),
+    }
+}
+
+/// Version 0 is a flat ring request, the fields of a JSON request file side by
+/// side. Request defaults fill in what it leaves out, so that version 1 lists
+/// every parameter; an output path is dropped.
+fn migrate_v0(document: Value) -> Result<Value> {
+    let Value::Object(mut fields) = document else {
+        bail!("A ring spec must be a table of fields");
+    };
+    fields.remove("version");
+    fields.remove("output_path");
+    let request: RingRequest = serde_json::from_value(Value::Object(fields))
+        .map_err(|e| anyhow!("Invalid version 0 spec: {}", e))?;
+
+    let mut spec = RingSpec::from_request(&request)?;
+    spec.metadata.created = None;
+    Ok(serde_json::to_value(spec)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{LensSeat, LensSurface, RingType, SeatType, Tessellation};
+
+    fn spec() -> RingSpec {
+        let mut ring = RingParameters::new(RingType::Concave, 50.0, 30.0).unwrap();
+        ring.lens_seat = Some(LensSeat {
+            radius_of_curvature: 40.0,
+            surface: LensSurface::Concave,
+            seat_type: SeatType::Toroidal { radius: Some(1.5) },
+            contact_diameter: Some(38.5),
+        });
+        ring.tessellation = Tessellation::ChordTolerance(0.02);
+        let mut spec = RingSpec::new(ring, ExportSettings { format: OutputFormat::ThreeMf, drawing: Some(DrawingFormat::Pdf) });
+        spec.metadata.name = Some("Doublet front".to_string());
+        spec
+    }
+
+    /// Specs compare by their JSON form; the version and generator are set on saving
+    fn same(a: &RingSpec, b: &RingSpec) -> bool {
+        let normalized = |spec: &RingSpec| {
+            let mut spec = spec.clone();
+            spec.metadata.generator = None;
+            serde_json::to_value(spec).unwrap()
+        };
+        normalized(a) == normalized(b)
+    }
+
+    #[test]
+    fn specs_round_trip_through_toml_and_json() {
+        let spec = spec();
+        for format in [SpecFormat::Toml, SpecFormat::Json] {
+            let text = spec.to_text(format).unwrap();
+            let read = RingSpec::parse(&text, format).unwrap();
+            assert!(same(&read, &spec), "{:?}:\n{}", format, text);
+            assert_eq!(read.version, SPEC_VERSION);
+            assert!(read.metadata.generator.unwrap().starts_with("Optics Ring Generator"));
+        }
+
+        // f32 values are written in their shortest form
+        let text = spec.to_text(SpecFormat::Toml).unwrap();
+        assert!(text.contains("chord_tolerance = 0.02\n"), "{}", text);
+    }
+
+    #[test]
+    fn version_0_request_migrates_with_every_parameter() {
+        let text = r#"{"ring_type": "CX", "outer_diameter": 60, "inner_diameter": 50, "height": 2.5,
+                       "chamfer_depth": 0.3, "output_path": "/tmp/out", "format": "obj"}"#;
+        assert_eq!(spec_version(text, SpecFormat::Json).unwrap(), 0);
+        let spec = RingSpec::parse(text, SpecFormat::Json).unwrap();
+
+        assert_eq!(spec.version, SPEC_VERSION);
+        assert_eq!((spec.ring.ring_type, spec.ring.height, spec.ring.chamfer_depth), (RingType::Convex, 2.5, 0.3));
+        assert_eq!(spec.export.format, OutputFormat::Obj);
+        assert!(spec.metadata.created.is_none());
+
+        // Defaults are written out, so the migrated spec no longer depends on them
+        let text = spec.to_text(SpecFormat::Toml).unwrap();
+        assert!(text.contains("top_step_height = "), "{}", text);
+        assert!(!text.contains("output_path"), "{}", text);
+        assert_eq!(spec_version(&text, SpecFormat::Toml).unwrap(), SPEC_VERSION);
+    }
+
+    #[test]
+    fn version_0_errors_name_the_problem() {
+        let error = RingSpec::parse(r#"ring_type = "CX"
+outer_diameter = 60
+inner_diameter = 50
+colour = "red""#, SpecFormat::Toml).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid version 0 spec: unknown field `colour`"), "{}", error);
+
+        let error = RingSpec::parse(r#"{"ring_type": "CX", "outer_diameter": 40, "inner_diameter": 50}"#, SpecFormat::Json)
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Invalid parameters:"), "{}", error);
+    }
+
+    #[test]
+    fn unsupported_documents_are_refused() {
+        let error = |text, format| RingSpec::parse(text, format).unwrap_err().to_string();
+        assert_eq!(
+            error("version = 2", SpecFormat::Toml),
+            format!("Spec version 2 is newer than this application supports (up to {})", SPEC_VERSION)
+        );
+        assert_eq!(error("version = \"one\"", SpecFormat::Toml), "Spec version must be a whole number, not \"one\"");
+        assert_eq!(error("[1, 2]", SpecFormat::Json), "A ring spec must be a table of fields");
+        assert!(error("version = ", SpecFormat::Toml).starts_with("Invalid TOML"));
+
+        let mut document: Value = serde_json::from_str(&spec().to_text(SpecFormat::Json).unwrap()).unwrap();
+        document["export"]["colour"] = "red".into();
+        let error = error(&document.to_string(), SpecFormat::Json);
+        assert!(error.starts_with("Invalid ring spec: unknown field `colour`"), "{}", error);
+    }
+
+    #[test]
+    fn format_follows_the_file_extension() {
+        assert_eq!(SpecFormat::for_path(Path::new("front.ring.toml")), SpecFormat::Toml);
+        assert_eq!(SpecFormat::for_path(Path::new("front.JSON")), SpecFormat::Json);
+        assert_eq!(SpecFormat::for_path(Path::new("front")), SpecFormat::Toml);
+    }
+}
@@ -0,0 +1,410 @@
+use anyhow::Result;
+
+use crate::geometry::{ring_profile, ProfilePoint, ProfileSegment, RingParameters};
+use crate::timestamp::utc_timestamp;
+
+/// Segments shorter than this are dropped from the B-rep (mm)
+const MIN_EDGE_LENGTH: f64 = 1e-6;
+
+/// Build a STEP AP214 file describing the ring as an exact B-rep.
+///
+/// Each profile segment revolved about Z becomes one analytic surface:
+/// horizontal lines give planes, vertical lines cylinders, sloped lines cones,
+/// arcs centered on the axis spheres and other arcs tori. Every revolution is
+/// split into two half faces so each edge is shared by exactly two faces.
+pub fn step_document(params: &RingParameters) -> Result<String> {
+    let mut profile = ring_profile(params)?;
+    if !profile.is_counter_clockwise() {
+        profile = profile.reversed();
+    }
+
+    let segments = profile.edges(MIN_EDGE_LENGTH as f32);
+    if segments.len() < 3 {
+        return Err(anyhow::anyhow!("Ring profile is degenerate"));
+    }
+
+    let name = params.file_stem();
+    let mut step = StepWriter::default();
+    let shape_representation = write_product(&mut step, &name);
+    let solid = write_brep(&mut step, &segments);
+    let context = write_context(&mut step);
+    let origin = step.axis_placement([0.0; 3], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+    step.set(
+        shape_representation,
+        format!("ADVANCED_BREP_SHAPE_REPRESENTATION('{}',(#{},#{}),#{})", name, solid, origin, context),
+    );
+
+    Ok(step.finish(&name))
+}
+
+/// STEP physical file: numbered entity instances under an ISO 10303-21 header
+#[derive(Default)]
+struct StepWriter {
+    entities: Vec<String>,
+}
+
+impl StepWriter {
+    /// Add an entity and return its instance number
+    fn add(&mut self, entity: String) -> usize {
+        self.entities.push(entity);
+        self.entities.len()
+    }
+
+    /// Reserve an instance number for an entity that refers to later ones
+    fn reserve(&mut self) -> usize {
+        self.add(String::new())
+    }
+
+    fn set(&mut self, id: usize, entity: String) {
+        self.entities[id - 1] = entity;
+    }
+
+    fn point(&mut self, p: [f64; 3]) -> usize {
+        self.add(format!("CARTESIAN_POINT('',({},{},{}))", real(p[0]), real(p[1]), real(p[2])))
+    }
+
+    fn direction(&mut self, d: [f64; 3]) -> usize {
+        self.add(format!("DIRECTION('',({},{},{}))", real(d[0]), real(d[1]), real(d[2])))
+    }
+
+    fn axis_placement(&mut self, location: [f64; 3], axis: [f64; 3], ref_direction: [f64; 3]) -> usize {
+        let location = self.point(location);
+        let axis = self.direction(axis);
+        let ref_direction = self.direction(ref_direction);
+        self.add(format!("AXIS2_PLACEMENT_3D('',#{},#{},#{})", location, axis, ref_direction))
+    }
+
+    fn finish(&self, name: &str) -> String {
+        let mut out = String::from("ISO-10303-21;\nHEADER;\n");
+        out.push_str("FILE_DESCRIPTION(('Optics support ring'),'2;1');\n");
+        out.push_str(&format!(
+            "FILE_NAME('{}.step','{}',(''),(''),'Optics Ring Generator','Optics Ring Generator','');\n",
+            name,
+            utc_timestamp()
+        ));
+        out.push_str("FILE_SCHEMA(('AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }'));\nENDSEC;\nDATA;\n");
+        for (index, entity) in self.entities.iter().enumerate() {
+            out.push_str(&format!("#{}={};\n", index + 1, entity));
+        }
+        out.push_str("ENDSEC;\nEND-ISO-10303-21;\n");
+        out
+    }
+}
+
+/// Product structure pointing at the shape; returns the reserved id of the shape representation
+fn write_product(step: &mut StepWriter, name: &str) -> usize {
+    let application = step.add("APPLICATION_CONTEXT('core data for automotive mechanical design processes')".to_string());
+    step.add(format!(
+        "APPLICATION_PROTOCOL_DEFINITION('international standard','automotive_design',2000,#{})",
+        application
+    ));
+    let product_context = step.add(format!("PRODUCT_CONTEXT('',#{},'mechanical')", application));
+    let product = step.add(format!("PRODUCT('{0}','{0}','',(#{1}))", name, product_context));
+    step.add(format!("PRODUCT_RELATED_PRODUCT_CATEGORY('part',$,(#{}))", product));
+    let formation = step.add(format!("PRODUCT_DEFINITION_FORMATION('','',#{})", product));
+    let definition_context = step.add(format!("PRODUCT_DEFINITION_CONTEXT('part definition',#{},'design')", application));
+    let definition = step.add(format!("PRODUCT_DEFINITION('design','',#{},#{})", formation, definition_context));
+    let shape = step.add(format!("PRODUCT_DEFINITION_SHAPE('','',#{})", definition));
+    let representation = step.reserve();
+    step.add(format!("SHAPE_DEFINITION_REPRESENTATION(#{},#{})", shape, representation));
+    representation
+}
+
+/// Millimetre and radian units with a 1e-5mm modelling tolerance; the profile is single precision
+fn write_context(step: &mut StepWriter) -> usize {
+    let length = step.add("( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) )".to_string());
+    let angle = step.add("( NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.) )".to_string());
+    let solid_angle = step.add("( NAMED_UNIT(*) SI_UNIT($,.STERADIAN.) SOLID_ANGLE_UNIT() )".to_string());
+    let uncertainty = step.add(format!(
+        "UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE(1.E-05),#{},'distance_accuracy_value','confusion accuracy')",
+        length
+    ));
+    step.add(format!(
+        "( GEOMETRIC_REPRESENTATION_CONTEXT(3) GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#{})) \
+         GLOBAL_UNIT_ASSIGNED_CONTEXT((#{},#{},#{})) REPRESENTATION_CONTEXT('Context #1','3D Context with UNIT and UNCERTAINTY') )",
+        uncertainty, length, angle, solid_angle
+    ))
+}
+
+/// Closed shell of the revolved profile; returns the MANIFOLD_SOLID_BREP id
+fn write_brep(step: &mut StepWriter, segments: &[(ProfilePoint, ProfileSegment)]) -> usize {
+    let count = segments.len();
+    // Profile vertex i is the start of segment i; half h covers angles [hπ, (h+1)π]
+    let at_angle = |(r, z): ProfilePoint, half: usize| -> [f64; 3] {
+        let sign = if half == 0 { 1.0 } else { -1.0 };
+        [sign * r as f64, 0.0, z as f64]
+    };
+
+    let vertices: Vec<[usize; 2]> = segments
+        .iter()
+        .map(|&(start, _)| {
+            [0, 1].map(|half| {
+                let point = step.point(at_angle(start, half));
+                step.add(format!("VERTEX_POINT('',#{})", point))
+            })
+        })
+        .collect();
+
+    // Half circles traced by each profile vertex, in the direction of increasing angle
+    let mut circles = vec![[0usize; 2]; count];
+    for (i, &((r, z), _)) in segments.iter().enumerate() {
+        let placement = step.axis_placement([0.0, 0.0, z as f64], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+        let circle = step.add(format!("CIRCLE('',#{},{})", placement, real(r as f64)));
+        for half in 0..2 {
+            circles[i][half] = step.add(format!(
+                "EDGE_CURVE('',#{},#{},#{},.T.)",
+                vertices[i][half],
+                vertices[i][1 - half],
+                circle
+            ));
+        }
+    }
+
+    // Copies of each profile segment at angles 0 and π, from its start to its end
+    let mut meridians = vec![[0usize; 2]; count];
+    for (i, &(start, segment)) in segments.iter().enumerate() {
+        let next = (i + 1) % count;
+        for half in 0..2 {
+            let from = at_angle(start, half);
+            let to = at_angle(segment.end(), half);
+            let curve = match segment {
+                ProfileSegment::Line { .. } => {
+                    let length = distance3(from, to);
+                    let point = step.point(from);
+                    let direction = step.direction([0, 1, 2].map(|k| (to[k] - from[k]) / length));
+                    let vector = step.add(format!("VECTOR('',#{},{})", direction, real(length)));
+                    step.add(format!("LINE('',#{},#{})", point, vector))
+                }
+                ProfileSegment::Arc { center, ccw, .. } => {
+                    let center = at_angle(center, half);
+                    let radius = distance3(center, from);
+                    // Counter-clockwise in the r–z plane turns about -e_θ, where e_θ is
+                    // the circumferential direction at this angle
+                    let e_theta = if half == 0 { 1.0 } else { -1.0 };
+                    let axis = if ccw { [0.0, -e_theta, 0.0] } else { [0.0, e_theta, 0.0] };
+                    let ref_direction = [0, 1, 2].map(|k| (from[k] - center[k]) / radius);
+                    let placement = step.axis_placement(center, axis, ref_direction);
+                    step.add(format!("CIRCLE('',#{},{})", placement, real(radius)))
+                }
+            };
+            meridians[i][half] = step.add(format!(
+                "EDGE_CURVE('',#{},#{},#{},.T.)",
+                vertices[i][half],
+                vertices[next][half],
+                curve
+            ));
+        }
+    }
+
+    // Each half face is bounded counter-clockwise in (angle, profile) coordinates,
+    // which faces outward because the profile is counter-clockwise in r–z
+    let mut faces = Vec::new();
+    for (i, &(start, segment)) in segments.iter().enumerate() {
+        let next = (i + 1) % count;
+        let (surface, same_sense) = write_surface(step, start, segment);
+        for half in 0..2 {
+            let edges = [
+                (circles[i][half], true),
+                (meridians[i][1 - half], true),
+                (circles[next][half], false),
+                (meridians[i][half], false),
+            ];
+            let oriented: Vec<String> = edges
+                .iter()
+                .map(|&(edge, forward)| {
+                    format!("#{}", step.add(format!("ORIENTED_EDGE('',*,*,#{},{})", edge, flag(forward))))
+                })
+                .collect();
+            let edge_loop = step.add(format!("EDGE_LOOP('',({}))", oriented.join(",")));
+            let bound = step.add(format!("FACE_OUTER_BOUND('',#{},.T.)", edge_loop));
+            faces.push(format!(
+                "#{}",
+                step.add(format!("ADVANCED_FACE('',(#{}),#{},{})", bound, surface, flag(same_sense)))
+            ));
+        }
+    }
+
+    let shell = step.add(format!("CLOSED_SHELL('',({}))", faces.join(",")));
+    step.add(format!("MANIFOLD_SOLID_BREP('',#{})", shell))
+}
+
+/// Surface swept by one profile segment, and whether its natural normal points
+/// out of the solid. Material lies to the left of a counter-clockwise profile,
+/// so the outward normal in r–z is (dz, -dr).
+fn write_surface(step: &mut StepWriter, start: ProfilePoint, segment: ProfileSegment) -> (usize, bool) {
+    let (r0, z0) = (start.0 as f64, start.1 as f64);
+    let (r1, z1) = (segment.end().0 as f64, segment.end().1 as f64);
+
+    match segment {
+        ProfileSegment::Line { .. } => {
+            let (dr, dz) = (r1 - r0, z1 - z0);
+            let outward_r = dz;
+            if dz.abs() < MIN_EDGE_LENGTH {
+                let placement = step.axis_placement([0.0, 0.0, z0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+                (step.add(format!("PLANE('',#{})", placement)), -dr > 0.0)
+            } else if dr.abs() < MIN_EDGE_LENGTH {
+                let placement = step.axis_placement([0.0, 0.0, z0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+                (step.add(format!("CYLINDRICAL_SURFACE('',#{},{})", placement, real(r0))), outward_r > 0.0)
+            } else {
+                // The cone axis points the way the radius grows
+                let axis = if dr / dz > 0.0 { 1.0 } else { -1.0 };
+                let semi_angle = (dr.abs() / dz.abs()).atan();
+                let placement = step.axis_placement([0.0, 0.0, z0], [0.0, 0.0, axis], [1.0, 0.0, 0.0]);
+                (
+                    step.add(format!("CONICAL_SURFACE('',#{},{},{})", placement, real(r0), real(semi_angle))),
+                    outward_r > 0.0,
+                )
+            }
+        }
+        ProfileSegment::Arc { center, ccw, .. } => {
+            let (cr, cz) = (center.0 as f64, center.1 as f64);
+            let radius = ((r0 - cr).powi(2) + (z0 - cz).powi(2)).sqrt();
+            let placement = step.axis_placement([0.0, 0.0, cz], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+            // Material is inside a counter-clockwise arc, so its outward normal points away from the center
+            if cr.abs() < MIN_EDGE_LENGTH {
+                (step.add(format!("SPHERICAL_SURFACE('',#{},{})", placement, real(radius))), ccw)
+            } else {
+                (
+                    step.add(format!("TOROIDAL_SURFACE('',#{},{},{})", placement, real(cr), real(radius))),
+                    ccw,
+                )
+            }
+        }
+    }
+}
+
+fn flag(value: bool) -> &'static str {
+    if value { ".T." } else { ".F." }
+}
+
+/// STEP real literal; always has a decimal point
+fn real(value: f64) -> String {
+    let value = if value.abs() < 1e-12 { 0.0 } else { value };
+    let text = format!("{:.9}", value);
+    let text = text.trim_end_matches('0');
+    text.to_string()
+}
+
+fn distance3(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|k| (a[k] - b[k]).powi(2)).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{LensSeat, LensSurface, RingType, SeatType};
+    use std::collections::{BTreeSet, HashMap};
+
+    /// Entity type and parameter text of every instance in the DATA section
+    fn entities(document: &str) -> HashMap<usize, (String, String)> {
+        let data = document.split("DATA;\n").nth(1).unwrap().split("ENDSEC;").next().unwrap();
+        data.lines()
+            .map(|line| {
+                let (id, entity) = line.trim_end_matches(';').split_once('=').unwrap();
+                let (name, parameters) = entity.split_once('(').unwrap_or_else(|| panic!("empty entity {}", id));
+                (id[1..].parse().unwrap(), (name.to_string(), parameters.to_string()))
+            })
+            .collect()
+    }
+
+    /// Instance numbers referred to in a parameter list
+    fn references(parameters: &str) -> Vec<usize> {
+        parameters
+            .split('#')
+            .skip(1)
+            .map(|rest| rest.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap())
+            .collect()
+    }
+
+    /// Check the document is a consistent closed shell and return its surface types
+    fn check_document(params: &RingParameters) -> BTreeSet<String> {
+        let document = step_document(params).unwrap();
+        assert!(document.starts_with("ISO-10303-21;\n"));
+        assert!(document.ends_with("END-ISO-10303-21;\n"));
+        let entities = entities(&document);
+        let label = params.file_stem();
+
+        for (name, parameters) in entities.values() {
+            for id in references(parameters) {
+                assert!(entities.contains_key(&id), "{}: {} refers to missing #{}", label, name, id);
+            }
+        }
+
+        // Two half faces per profile segment, all in the one shell
+        let mut profile = ring_profile(params).unwrap();
+        if !profile.is_counter_clockwise() {
+            profile = profile.reversed();
+        }
+        let segment_count = profile.edges(MIN_EDGE_LENGTH as f32).len();
+        let faces: BTreeSet<usize> =
+            entities.iter().filter(|(_, (name, _))| name == "ADVANCED_FACE").map(|(&id, _)| id).collect();
+        assert_eq!(faces.len(), 2 * segment_count, "{}: face count", label);
+        let shells: Vec<&String> =
+            entities.values().filter(|(name, _)| name == "CLOSED_SHELL").map(|(_, p)| p).collect();
+        assert_eq!(shells.len(), 1);
+        assert_eq!(references(shells[0]).into_iter().collect::<BTreeSet<_>>(), faces, "{}: shell faces", label);
+
+        // Every edge bounds exactly two faces, once in each direction
+        let mut uses: HashMap<usize, Vec<bool>> = HashMap::new();
+        for (name, parameters) in entities.values() {
+            if name == "ORIENTED_EDGE" {
+                uses.entry(references(parameters)[0]).or_default().push(parameters.contains(".T."));
+            }
+        }
+        let edge_curves = entities.values().filter(|(name, _)| name == "EDGE_CURVE").count();
+        assert_eq!(uses.len(), edge_curves, "{}: unused edges", label);
+        for (edge, mut directions) in uses {
+            directions.sort();
+            assert_eq!(directions, [false, true], "{}: edge #{} is not shared by two faces", label, edge);
+        }
+
+        let surfaces = faces.iter().map(|id| references(&entities[id].1)[1]);
+        surfaces.map(|id| entities[&id].0.clone()).collect()
+    }
+
+    fn surface_set(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn convex_ring_is_planes_cylinders_and_cones() {
+        let params = RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap();
+        assert_eq!(check_document(&params), surface_set(&["CONICAL_SURFACE", "CYLINDRICAL_SURFACE", "PLANE"]));
+
+        let mut params = RingParameters::with_defaults(RingType::Convex, 50.0, 40.0);
+        params.lens_seat = Some(LensSeat {
+            radius_of_curvature: 60.0,
+            surface: LensSurface::Convex,
+            seat_type: SeatType::Toroidal { radius: None },
+            contact_diameter: None,
+        });
+        params.validate().unwrap();
+        assert!(check_document(&params).contains("TOROIDAL_SURFACE"));
+    }
+
+    #[test]
+    fn concave_ring_dish_is_a_torus() {
+        let params = RingParameters::new(RingType::Concave, 50.0, 40.0).unwrap();
+        assert_eq!(check_document(&params), surface_set(&["CYLINDRICAL_SURFACE", "PLANE", "TOROIDAL_SURFACE"]));
+    }
+
+    #[test]
+    fn spherical_seat_is_a_sphere() {
+        let mut params = RingParameters::with_defaults(RingType::Concave, 50.0, 40.0);
+        params.lens_seat = Some(LensSeat {
+            radius_of_curvature: 60.0,
+            surface: LensSurface::Concave,
+            seat_type: SeatType::Spherical,
+            contact_diameter: None,
+        });
+        params.validate().unwrap();
+        assert_eq!(check_document(&params), surface_set(&["CYLINDRICAL_SURFACE", "PLANE", "SPHERICAL_SURFACE"]));
+    }
+
+    #[test]
+    fn point_contact_ring_is_refused() {
+        let params = RingParameters::new(RingType::ThreePoint, 50.0, 40.0).unwrap();
+        let error = step_document(&params).unwrap_err().to_string();
+        assert!(error.contains("3P rings are not solids of revolution"), "{}", error);
+    }
+}
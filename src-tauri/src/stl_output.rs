@@ -1,114 +1,768 @@
 use anyhow::Result;
-use std::fs::File;
-use std::path::Path;
-use stl_io::write_stl;
-
-use crate::geometry::{RingParameters, generate_ring_mesh};
-
-/// Generate and save an STL file for the given ring parameters
-pub fn generate_stl_file(params: &RingParameters, output_dir: Option<&str>) -> Result<String> {
-    println!("Generating {} ring geometry...", params.ring_type);
-    println!("  Outer diameter: {:.1}mm", params.outer_diameter);
-    println!("  Inner diameter: {:.1}mm", params.inner_diameter);
-    println!("  Height: {:.1}mm", params.height);
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::drawing::{drawing_document, DrawingFormat};
+use crate::geometry::{
+    LensSeat, LensSurface, PadTip, RingParameters, RingType, SeatType, Tessellation, generate_ring_mesh,
+    DEFAULT_CHORD_TOLERANCE,
+};
+use crate::gltf_output::glb_document;
+use crate::mesh::Mesh;
+use crate::profile_output::{dxf_document, svg_document};
+use crate::step_output::step_document;
+use crate::three_mf::write_3mf;
+
+/// Generate and save an STL file for the given ring parameters, named after the
+/// ring unless a file stem is given. Returns the path and the triangle count.
+pub fn generate_stl_file(
+    params: &RingParameters,
+    output_dir: Option<&str>,
+    file_stem: Option<&str>,
+) -> Result<(String, usize)> {
+    eprintln!("Generating {} ring geometry...", params.type_code());
+    eprintln!("  Outer diameter: {:.1}mm", params.outer_diameter);
+    eprintln!("  Inner diameter: {:.1}mm", params.inner_diameter);
+    eprintln!("  Height: {:.1}mm", params.height);
     
     // Generate the mesh
-    let triangles = generate_ring_mesh(params)?;
-    println!("  Generated {} triangles", triangles.len());
+    let mesh = generate_ring_mesh(params)?;
+    eprintln!("  Generated {} triangles", mesh.triangle_count());
     
-    // Determine output path
-    let filename = params.filename();
-    let output_path = if let Some(dir) = output_dir {
-        Path::new(dir).join(&filename)
-    } else {
-        Path::new(&filename).to_path_buf()
+    let format = OutputFormat::Stl;
+    let filename = match file_stem {
+        Some(stem) => format!("{}.{}", stem, format.extension()),
+        None => params.filename(format.extension()),
     };
-    
-    // Create output directory if it doesn't exist
-    if let Some(parent) = output_path.parent() {
-        std::fs::create_dir_all(parent)?;
+    let path = write_mesh_file(&mesh, params, &filename, output_dir, format)?;
+    Ok((path, mesh.triangle_count()))
+}
+
+/// Marks the parameter header of STL files written by this app
+const HEADER_TAG: &str = "ORG";
+/// Last word of a parameter header that had no room for every entry
+const TRUNCATED_MARK: &str = "+";
+/// Size of the header of a binary STL file (bytes)
+const BINARY_HEADER_SIZE: usize = 80;
+
+/// File format written for a generated ring; serialized under the names
+/// `from_str` accepts
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Binary STL with the ring parameters in its header
+    #[default]
+    Stl,
+    /// ASCII STL with a named solid
+    StlAscii,
+    /// 3MF package with units, metadata and a thumbnail
+    #[serde(rename = "3mf")]
+    ThreeMf,
+    /// Wavefront OBJ
+    Obj,
+    /// ASCII PLY
+    PlyAscii,
+    /// Binary little-endian PLY
+    #[serde(rename = "ply", alias = "ply-binary")]
+    PlyBinary,
+    /// Binary glTF 2.0
+    Glb,
+    /// STEP AP214 B-rep with exact surfaces (CX and CC rings only)
+    Step,
+    /// Cross-section profile as DXF lines and arcs (CX and CC rings only)
+    Dxf,
+    /// Cross-section profile as SVG (CX and CC rings only)
+    Svg,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "stl" => Ok(OutputFormat::Stl),
+            "stl-ascii" => Ok(OutputFormat::StlAscii),
+            "3mf" => Ok(OutputFormat::ThreeMf),
+            "obj" => Ok(OutputFormat::Obj),
+            "ply-ascii" => Ok(OutputFormat::PlyAscii),
+            "ply" | "ply-binary" => Ok(OutputFormat::PlyBinary),
+            "glb" => Ok(OutputFormat::Glb),
+            "step" | "stp" => Ok(OutputFormat::Step),
+            "dxf" => Ok(OutputFormat::Dxf),
+            "svg" => Ok(OutputFormat::Svg),
+            _ => Err(anyhow::anyhow!(
+                "Invalid output format: {}. Valid formats are: stl, stl-ascii, 3mf, obj, ply, ply-ascii, glb, step, dxf, svg",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Stl => write!(f, "stl"),
+            OutputFormat::StlAscii => write!(f, "stl-ascii"),
+            OutputFormat::ThreeMf => write!(f, "3mf"),
+            OutputFormat::Obj => write!(f, "obj"),
+            OutputFormat::PlyAscii => write!(f, "ply-ascii"),
+            OutputFormat::PlyBinary => write!(f, "ply"),
+            OutputFormat::Glb => write!(f, "glb"),
+            OutputFormat::Step => write!(f, "step"),
+            OutputFormat::Dxf => write!(f, "dxf"),
+            OutputFormat::Svg => write!(f, "svg"),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Stl | OutputFormat::StlAscii => "stl",
+            OutputFormat::ThreeMf => "3mf",
+            OutputFormat::Obj => "obj",
+            OutputFormat::PlyAscii | OutputFormat::PlyBinary => "ply",
+            OutputFormat::Glb => "glb",
+            OutputFormat::Step => "step",
+            OutputFormat::Dxf => "dxf",
+            OutputFormat::Svg => "svg",
+        }
+    }
+
+    /// Media type of the file, for HTTP responses
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Stl | OutputFormat::StlAscii => "model/stl",
+            OutputFormat::ThreeMf => "model/3mf",
+            OutputFormat::Obj => "model/obj",
+            OutputFormat::PlyAscii | OutputFormat::PlyBinary => "application/octet-stream",
+            OutputFormat::Glb => "model/gltf-binary",
+            OutputFormat::Step => "model/step",
+            OutputFormat::Dxf => "image/vnd.dxf",
+            OutputFormat::Svg => "image/svg+xml",
+        }
+    }
+
+    /// Encoder for this format
+    pub fn writer(&self) -> Box<dyn MeshWriter> {
+        match self {
+            OutputFormat::Stl => Box::new(StlWriter { ascii: false }),
+            OutputFormat::StlAscii => Box::new(StlWriter { ascii: true }),
+            OutputFormat::ThreeMf => Box::new(ThreeMfWriter),
+            OutputFormat::Obj => Box::new(ObjWriter),
+            OutputFormat::PlyAscii => Box::new(PlyWriter { binary: false }),
+            OutputFormat::PlyBinary => Box::new(PlyWriter { binary: true }),
+            OutputFormat::Glb => Box::new(GlbWriter),
+            OutputFormat::Step => Box::new(StepWriter),
+            OutputFormat::Dxf => Box::new(DxfWriter),
+            OutputFormat::Svg => Box::new(SvgWriter),
+        }
+    }
+}
+
+/// Encodes a generated ring into one output file format, streaming it to any
+/// writer. Mesh formats write the triangles; CAD and profile formats rebuild
+/// the exact shape from the parameters.
+pub trait MeshWriter {
+    fn write(&self, out: &mut dyn Write, mesh: &Mesh, params: &RingParameters) -> Result<()>;
+}
+
+/// STL carrying the ring parameters: in the 80-byte header of a binary file,
+/// or after the solid name of an ASCII file, so `read_stl_parameters` can recover them
+pub struct StlWriter {
+    pub ascii: bool,
+}
+
+impl MeshWriter for StlWriter {
+    fn write(&self, out: &mut dyn Write, mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        let triangles = mesh.to_stl_triangles();
+
+        if self.ascii {
+            let name = params.file_stem();
+            writeln!(out, "solid {} {}", name, parameter_header(params, usize::MAX))?;
+            for triangle in &triangles {
+                let n = &triangle.normal;
+                writeln!(out, "  facet normal {} {} {}\n    outer loop", n[0], n[1], n[2])?;
+                for v in &triangle.vertices {
+                    writeln!(out, "      vertex {} {} {}", v[0], v[1], v[2])?;
+                }
+                writeln!(out, "    endloop\n  endfacet")?;
+            }
+            writeln!(out, "endsolid {}", name)?;
+        } else {
+            write_binary_stl(out, &triangles, &parameter_header(params, BINARY_HEADER_SIZE))?;
+        }
+        Ok(())
+    }
+}
+
+/// Binary STL with the given header text, cut to the 80 header bytes
+fn write_binary_stl(out: &mut dyn Write, triangles: &[stl_io::Triangle], text: &str) -> Result<()> {
+    let mut header = [0u8; BINARY_HEADER_SIZE];
+    let length = text.len().min(BINARY_HEADER_SIZE);
+    header[..length].copy_from_slice(&text.as_bytes()[..length]);
+    out.write_all(&header)?;
+    out.write_all(&(triangles.len() as u32).to_le_bytes())?;
+    for triangle in triangles {
+        for v in std::iter::once(&triangle.normal).chain(&triangle.vertices) {
+            for k in 0..3 {
+                out.write_all(&v[k].to_le_bytes())?;
+            }
+        }
+        // Attribute byte count, unused
+        out.write_all(&[0, 0])?;
+    }
+    Ok(())
+}
+
+pub struct ThreeMfWriter;
+
+impl MeshWriter for ThreeMfWriter {
+    fn write(&self, out: &mut dyn Write, mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        // The zip directory records entry offsets, so the package is put together in memory
+        let mut package = Cursor::new(Vec::new());
+        write_3mf(&mut package, mesh, params)?;
+        out.write_all(&package.into_inner())?;
+        Ok(())
+    }
+}
+
+/// Wavefront OBJ with shared vertices, one object named after the part
+pub struct ObjWriter;
+
+impl MeshWriter for ObjWriter {
+    fn write(&self, out: &mut dyn Write, mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        writeln!(out, "# Optics Ring Generator, units: mm")?;
+        writeln!(out, "o {}", params.file_stem())?;
+        for [x, y, z] in &mesh.vertices {
+            writeln!(out, "v {} {} {}", x, y, z)?;
+        }
+        // OBJ indices start at 1
+        for [a, b, c] in &mesh.indices {
+            writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1)?;
+        }
+        Ok(())
     }
+}
+
+/// Stanford PLY with float vertices and triangle faces, ASCII or binary little-endian
+pub struct PlyWriter {
+    pub binary: bool,
+}
+
+impl MeshWriter for PlyWriter {
+    fn write(&self, out: &mut dyn Write, mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        let encoding = if self.binary { "binary_little_endian" } else { "ascii" };
+        writeln!(out, "ply\nformat {} 1.0", encoding)?;
+        writeln!(out, "comment Optics Ring Generator {}, units: mm", params.file_stem())?;
+        writeln!(out, "element vertex {}\nproperty float x\nproperty float y\nproperty float z", mesh.vertex_count())?;
+        writeln!(out, "element face {}\nproperty list uchar uint vertex_indices\nend_header", mesh.triangle_count())?;
+
+        if self.binary {
+            for vertex in &mesh.vertices {
+                for value in vertex {
+                    out.write_all(&value.to_le_bytes())?;
+                }
+            }
+            for triangle in &mesh.indices {
+                out.write_all(&[3])?;
+                for index in triangle {
+                    out.write_all(&index.to_le_bytes())?;
+                }
+            }
+        } else {
+            for [x, y, z] in &mesh.vertices {
+                writeln!(out, "{} {} {}", x, y, z)?;
+            }
+            for [a, b, c] in &mesh.indices {
+                writeln!(out, "3 {} {} {}", a, b, c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct GlbWriter;
+
+impl MeshWriter for GlbWriter {
+    fn write(&self, out: &mut dyn Write, mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        out.write_all(&glb_document(mesh, params)?)?;
+        Ok(())
+    }
+}
+
+pub struct StepWriter;
+
+impl MeshWriter for StepWriter {
+    fn write(&self, out: &mut dyn Write, _mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        out.write_all(step_document(params)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+pub struct DxfWriter;
+
+impl MeshWriter for DxfWriter {
+    fn write(&self, out: &mut dyn Write, _mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        out.write_all(dxf_document(params)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+pub struct SvgWriter;
+
+impl MeshWriter for SvgWriter {
+    fn write(&self, out: &mut dyn Write, _mesh: &Mesh, params: &RingParameters) -> Result<()> {
+        out.write_all(svg_document(params)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Save an already generated ring mesh in the given format
+pub fn save_mesh_file(
+    mesh: &Mesh,
+    params: &RingParameters,
+    output_dir: Option<&str>,
+    format: OutputFormat,
+) -> Result<String> {
+    write_mesh_file(mesh, params, &params.filename(format.extension()), output_dir, format)
+}
+
+/// Save an already generated ring mesh under the given file name
+pub fn write_mesh_file(
+    mesh: &Mesh,
+    params: &RingParameters,
+    filename: &str,
+    output_dir: Option<&str>,
+    format: OutputFormat,
+) -> Result<String> {
+    let output_path = output_path(filename, output_dir)?;
     
-    // Write STL file
-    let mut file = File::create(&output_path)?;
-    write_stl(&mut file, triangles.iter())?;
+    // Stream into a temporary file so a failed export leaves no partial file behind
+    write_complete_file_with(&output_path, |out| format.writer().write(out, mesh, params))?;
     
     let output_path_str = output_path.to_string_lossy().to_string();
-    println!("  STL file saved: {}", output_path_str);
+    eprintln!("  {} file saved: {}", format.extension().to_uppercase(), output_path_str);
     
     Ok(output_path_str)
 }
 
-/// Validate ring parameters for 3D printing
-pub fn validate_for_printing(params: &RingParameters) -> Result<()> {
-    let wall_thickness = (params.outer_diameter - params.inner_diameter) / 2.0;
+/// Save a binary STL that holds several parts, such as a plate of rings. The
+/// header is free text, so it is not read back as ring parameters.
+pub fn save_plate_stl(mesh: &Mesh, header: &str, filename: &str, output_dir: Option<&str>) -> Result<String> {
+    let output_path = output_path(filename, output_dir)?;
+
+    let mut data = Vec::new();
+    write_binary_stl(&mut data, &mesh.to_stl_triangles(), header)?;
+    write_complete_file(&output_path, &data)?;
+
+    let output_path_str = output_path.to_string_lossy().to_string();
+    eprintln!("  Plate STL saved: {}", output_path_str);
+
+    Ok(output_path_str)
+}
+
+/// Save the technical drawing of the ring, named after the part, next to its other files
+pub fn save_drawing(params: &RingParameters, output_dir: Option<&str>, format: DrawingFormat) -> Result<String> {
+    let filename = format!("{}-drawing.{}", params.file_stem(), format.extension());
+    let output_path = output_path(&filename, output_dir)?;
     
-    // Check minimum wall thickness for 3D printing (typically 1mm minimum)
-    if wall_thickness < 1.0 {
-        return Err(anyhow::anyhow!(
-            "Wall thickness ({:.2}mm) is too thin for reliable 3D printing. Minimum recommended: 1.0mm",
-            wall_thickness
-        ));
-    }
+    let data = drawing_document(params, format)?;
+    write_complete_file(&output_path, &data)?;
     
-    // Check maximum reasonable size (300mm diameter should be enough for most printers)
-    if params.outer_diameter > 300.0 {
-        return Err(anyhow::anyhow!(
-            "Outer diameter ({:.1}mm) exceeds typical 3D printer build volume",
-            params.outer_diameter
-        ));
+    let output_path_str = output_path.to_string_lossy().to_string();
+    eprintln!("  Drawing saved: {}", output_path_str);
+    
+    Ok(output_path_str)
+}
+
+/// Path of an output file, creating its directory if it doesn't exist
+fn output_path(filename: &str, output_dir: Option<&str>) -> Result<PathBuf> {
+    let output_path = if let Some(dir) = output_dir {
+        Path::new(dir).join(filename)
+    } else {
+        Path::new(filename).to_path_buf()
+    };
+    
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
     }
     
-    // Check minimum practical size
-    if params.inner_diameter < 5.0 {
+    Ok(output_path)
+}
+
+/// Write through a temporary file in the same directory and rename it into
+/// place, so an interrupted or cancelled job never leaves a half-written file
+pub(crate) fn write_complete_file(path: &Path, data: &[u8]) -> Result<()> {
+    write_complete_file_with(path, |out| Ok(out.write_all(data)?))
+}
+
+/// Like `write_complete_file`, with the contents streamed by `write`
+pub(crate) fn write_complete_file_with(path: &Path, write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+    let filename = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.part", filename));
+    let written = std::fs::File::create(&temp_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| {
+            let mut out = std::io::BufWriter::new(file);
+            write(&mut out)?;
+            Ok(out.flush()?)
+        })
+        .and_then(|_| Ok(std::fs::rename(&temp_path, path)?));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Compact parameter string such as `ORG CX OD=50 ID=40 H=22`, at most `max_length`
+/// bytes. Besides type and size it only lists what differs from a fresh ring of
+/// that size, which is where `read_stl_parameters` starts from. Entries run from
+/// the most to the least important; when some do not fit they are left out and
+/// the header ends in `TRUNCATED_MARK`, so the file is not read back as a
+/// different ring.
+pub fn parameter_header(params: &RingParameters, max_length: usize) -> String {
+    let (od, id, height) = (params.outer_diameter, params.inner_diameter, params.height);
+    let defaults = RingParameters::with_defaults(params.ring_type, od, id).with_height(height);
+    let default = |field: fn(&RingParameters) -> f32| Some(field(&defaults));
+    let mut entries = vec![format!("OD={}", od), format!("ID={}", id), format!("H={}", height)];
+
+    match params.ring_type {
+        RingType::Convex => entries.extend([
+            header_entry("T", params.top_step_height, default(|d| d.top_step_height)),
+            header_entry("B", params.bottom_step_height, default(|d| d.bottom_step_height)),
+            header_entry("M", params.middle_step_height, default(|d| d.middle_step_height)),
+            header_entry("W", params.inner_step_width, default(|d| d.inner_step_width)),
+            header_entry("C", params.chamfer_depth, default(|d| d.chamfer_depth)),
+        ].into_iter().flatten()),
+        RingType::Concave => {}
+        RingType::ThreePoint => {
+            entries.extend([
+                header_entry("A", params.contact_offset_deg, Some(0.0)),
+                params.pad_radius.and_then(|radius| header_entry("PR", radius, None)),
+                header_entry("PW", params.pad_width, default(|d| d.pad_width)),
+                header_entry("PH", params.pad_height, default(|d| d.pad_height)),
+            ].into_iter().flatten());
+            if params.pad_tip != PadTip::Flat {
+                entries.push(format!("TIP={}", params.pad_tip));
+            }
+        }
+    }
+
+    // Seat words use the short forms the parsers accept
+    if let Some(seat) = params.lens_seat {
+        entries.extend(header_entry("LR", seat.radius_of_curvature, None));
+        if seat.surface != default_lens_surface(params.ring_type) {
+            entries.push(format!("LENS={}", match seat.surface {
+                LensSurface::Convex => "CX",
+                LensSurface::Concave => "CC",
+            }));
+        }
+        match seat.seat_type {
+            SeatType::Spherical => {}
+            SeatType::Tangent => entries.push("SEAT=cone".to_string()),
+            SeatType::SharpCorner => entries.push("SEAT=sharp".to_string()),
+            SeatType::Toroidal { radius } => {
+                entries.push("SEAT=torus".to_string());
+                entries.extend(radius.and_then(|radius| header_entry("TR", radius, None)));
+            }
+        }
+        entries.extend(seat.contact_diameter.and_then(|diameter| header_entry("CD", diameter, None)));
+    }
+
+    entries.extend(match params.tessellation {
+        Tessellation::ChordTolerance(tolerance) => header_entry("TOL", tolerance, Some(DEFAULT_CHORD_TOLERANCE)),
+        Tessellation::AngularTolerance(degrees) => header_entry("ANG", degrees, None),
+    });
+
+    let mut header = format!("{} {}", HEADER_TAG, params.type_code());
+    let full_length = header.len() + entries.iter().map(|entry| 1 + entry.len()).sum::<usize>();
+    if full_length <= max_length {
+        for entry in entries {
+            header.push(' ');
+            header.push_str(&entry);
+        }
+        return header;
+    }
+
+    let room = max_length.saturating_sub(1 + TRUNCATED_MARK.len());
+    for entry in entries {
+        if header.len() + 1 + entry.len() <= room {
+            header.push(' ');
+            header.push_str(&entry);
+        }
+    }
+    header.push(' ');
+    header.push_str(TRUNCATED_MARK);
+    header
+}
+
+/// `KEY=value` entry of the parameter header, or nothing when the value is the default
+fn header_entry(key: &str, value: f32, default: Option<f32>) -> Option<String> {
+    (default != Some(value)).then(|| format!("{}={}", key, value))
+}
+
+/// Recover the parameters of a ring from the contents of an STL file this app wrote
+pub fn read_stl_parameters(data: &[u8]) -> Result<RingParameters> {
+    // A binary file is exactly as long as its triangle count says, even if its header starts with "solid"
+    let is_binary = data.len() >= BINARY_HEADER_SIZE + 4 && {
+        let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+        data.len() == BINARY_HEADER_SIZE + 4 + count * 50
+    };
+    let header = if is_binary {
+        let header = &data[..BINARY_HEADER_SIZE];
+        let end = header.iter().position(|&b| b == 0).unwrap_or(BINARY_HEADER_SIZE);
+        String::from_utf8_lossy(&header[..end]).into_owned()
+    } else {
+        let end = data.iter().position(|&b| b == b'\n').unwrap_or(data.len());
+        String::from_utf8_lossy(&data[..end]).into_owned()
+    };
+
+    let start = header
+        .split_whitespace()
+        .position(|word| word == HEADER_TAG)
+        .ok_or_else(|| anyhow::anyhow!("The STL file has no ring parameters; it was not written by this app"))?;
+    let words: Vec<&str> = header.split_whitespace().skip(start + 1).collect();
+    parse_parameter_header(&words)
+}
+
+/// Ring parameters from the words after the header tag: the type code, then KEY=VALUE entries
+fn parse_parameter_header(words: &[&str]) -> Result<RingParameters> {
+    let (code, entries) = words
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("The STL parameter header has no ring type"))?;
+    let (ring_type, contact_count) = match code.strip_suffix('P').and_then(|n| n.parse::<u32>().ok()) {
+        Some(count) => (RingType::ThreePoint, Some(count)),
+        None => (code.parse::<RingType>()?, None),
+    };
+    if entries.last() == Some(&TRUNCATED_MARK) {
         return Err(anyhow::anyhow!(
-            "Inner diameter ({:.1}mm) is too small for practical use",
-            params.inner_diameter
+            "The STL header had no room for every parameter of this ring, so it cannot be \
+             restored from it; save the ring as ASCII STL or as a ring spec instead"
         ));
     }
-    
-    println!("✓ Ring parameters validated for 3D printing");
-    println!("  Wall thickness: {:.2}mm", wall_thickness);
-    
-    Ok(())
+
+    let mut values = HashMap::new();
+    for entry in entries {
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid entry in the STL parameter header: {}", entry))?;
+        values.insert(key, value);
+    }
+    let number = |key: &str| -> Result<Option<f32>> {
+        values
+            .get(key)
+            .map(|value| value.parse::<f32>().map_err(|_| anyhow::anyhow!("Invalid {} in the STL parameter header: {}", key, value)))
+            .transpose()
+    };
+    let required = |key: &str| -> Result<f32> {
+        number(key)?.ok_or_else(|| anyhow::anyhow!("The STL parameter header has no {}", key))
+    };
+
+    let mut params = RingParameters::with_defaults(ring_type, required("OD")?, required("ID")?);
+    if let Some(height) = number("H")? {
+        params = params.with_height(height);
+    }
+    params.contact_count = contact_count.unwrap_or(params.contact_count);
+    params.top_step_height = number("T")?.unwrap_or(params.top_step_height);
+    params.bottom_step_height = number("B")?.unwrap_or(params.bottom_step_height);
+    params.middle_step_height = number("M")?.unwrap_or(params.middle_step_height);
+    params.inner_step_width = number("W")?.unwrap_or(params.inner_step_width);
+    params.chamfer_depth = number("C")?.unwrap_or(params.chamfer_depth);
+    params.contact_offset_deg = number("A")?.unwrap_or(params.contact_offset_deg);
+    params.pad_radius = number("PR")?.or(params.pad_radius);
+    params.pad_width = number("PW")?.unwrap_or(params.pad_width);
+    params.pad_height = number("PH")?.unwrap_or(params.pad_height);
+    if let Some(tip) = values.get("TIP") {
+        params.pad_tip = tip.parse::<PadTip>()?;
+    }
+    if let Some(tolerance) = number("TOL")? {
+        params.tessellation = Tessellation::ChordTolerance(tolerance);
+    }
+    if let Some(degrees) = number("ANG")? {
+        params.tessellation = Tessellation::AngularTolerance(degrees);
+    }
+    if let Some(radius_of_curvature) = number("LR")? {
+        let surface = match values.get("LENS") {
+            Some(surface) => surface.parse::<LensSurface>()?,
+            None => default_lens_surface(ring_type),
+        };
+        let mut seat_type = match values.get("SEAT") {
+            Some(seat_type) => seat_type.parse::<SeatType>()?,
+            None => SeatType::Spherical,
+        };
+        if let (SeatType::Toroidal { radius }, Some(torus_radius)) = (&mut seat_type, number("TR")?) {
+            *radius = Some(torus_radius);
+        }
+        params.lens_seat = Some(LensSeat {
+            radius_of_curvature,
+            surface,
+            seat_type,
+            contact_diameter: number("CD")?,
+        });
+    }
+
+    const KNOWN_KEYS: [&str; 20] = [
+        "OD", "ID", "H", "T", "B", "M", "W", "C", "A", "PR", "PW", "PH", "TIP", "TOL", "ANG", "LR", "LENS", "SEAT",
+        "TR", "CD",
+    ];
+    if let Some(key) = values.keys().find(|key| !KNOWN_KEYS.contains(key)) {
+        return Err(anyhow::anyhow!("Unknown entry in the STL parameter header: {}", key));
+    }
+    params.validate()?;
+
+    Ok(params)
 }
 
-/// Print estimated printing information
-pub fn print_manufacturing_info(params: &RingParameters) {
-    let wall_thickness = (params.outer_diameter - params.inner_diameter) / 2.0;
-    let outer_radius = params.outer_diameter / 2.0;
-    let inner_radius = params.inner_diameter / 2.0;
-    
-    // Estimate material volume (simplified calculation)
-    let outer_volume = std::f32::consts::PI * outer_radius.powi(2) * params.height;
-    let inner_volume = std::f32::consts::PI * inner_radius.powi(2) * params.height;
-    let material_volume = outer_volume - inner_volume;
-    
-    println!("\n3D Printing Information:");
-    println!("  Wall thickness: {:.2}mm", wall_thickness);
-    println!("  Estimated material volume: {:.2}cm³", material_volume / 1000.0);
-    
-    // Printing recommendations
-    println!("\nRecommended Print Settings:");
-    match params.ring_type {
-        crate::geometry::RingType::Convex => {
-            println!("  - Layer height: 0.15-0.2mm for smooth curves");
-            println!("  - Support: None required");
-            println!("  - Orientation: Place flat on build plate");
-        },
-        crate::geometry::RingType::Concave => {
-            println!("  - Layer height: 0.15-0.2mm for smooth curves");
-            println!("  - Support: Light support for overhangs");
-            println!("  - Orientation: Place flat on build plate");
-        },
-        crate::geometry::RingType::ThreePoint => {
-            println!("  - Layer height: 0.2-0.3mm (structural print)");
-            println!("  - Support: Minimal support for contact points");
-            println!("  - Orientation: Place flat on build plate");
-        },
+/// Lens surface a seat carries when none is given: concave for CC rings, convex otherwise
+fn default_lens_surface(ring_type: RingType) -> LensSurface {
+    match ring_type {
+        RingType::Concave => LensSurface::Concave,
+        _ => LensSurface::Convex,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_overrides_apply_before_validation() {
+        let params = parse_parameter_header(&["CX", "OD=60", "ID=50", "H=2.5", "C=0.3"]).unwrap();
+        assert_eq!(params.chamfer_depth, 0.3);
+    }
+
+    fn stl_round_trip(params: &RingParameters, ascii: bool) -> Result<RingParameters> {
+        let mesh = generate_ring_mesh(params)?;
+        let mut data = Cursor::new(Vec::new());
+        StlWriter { ascii }.write(&mut data, &mesh, params)?;
+        read_stl_parameters(&data.into_inner())
+    }
+
+    #[test]
+    fn binary_header_round_trips_non_default_tolerance() {
+        let mut params = RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap();
+        params.tessellation = Tessellation::ChordTolerance(0.02);
+        let read = stl_round_trip(&params, false).unwrap();
+        assert_eq!(format!("{:?}", read), format!("{:?}", params));
+    }
+
+    #[test]
+    fn truncated_binary_header_is_refused_not_defaulted() {
+        let mut params = RingParameters::with_defaults(RingType::Convex, 152.4, 140.35).with_height(22.5);
+        params.top_step_height = 1.25;
+        params.bottom_step_height = 1.75;
+        params.inner_step_width = 2.25;
+        params.chamfer_depth = 0.35;
+        params.lens_seat = Some(LensSeat {
+            radius_of_curvature: 180.5,
+            surface: LensSurface::Convex,
+            seat_type: SeatType::Toroidal { radius: Some(1.5) },
+            contact_diameter: Some(142.75),
+        });
+        params.tessellation = Tessellation::ChordTolerance(0.02);
+        params.validate().unwrap();
+
+        let header = parameter_header(&params, BINARY_HEADER_SIZE);
+        assert!(header.len() <= BINARY_HEADER_SIZE && header.ends_with(" +"), "{}", header);
+        let error = stl_round_trip(&params, false).unwrap_err();
+        assert!(error.to_string().contains("no room"), "{}", error);
+
+        // ASCII headers have no size limit
+        let read = stl_round_trip(&params, true).unwrap();
+        assert_eq!(format!("{:?}", read), format!("{:?}", params));
+    }
+
+    fn encode(format: OutputFormat, mesh: &Mesh, params: &RingParameters) -> Vec<u8> {
+        let mut data = Vec::new();
+        format.writer().write(&mut data, mesh, params).unwrap();
+        data
+    }
+
+    fn ring() -> (RingParameters, Mesh) {
+        let params = RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap();
+        let mesh = generate_ring_mesh(&params).unwrap();
+        (params, mesh)
+    }
+
+    #[test]
+    fn obj_lists_every_vertex_and_face() {
+        let (params, mesh) = ring();
+        let obj = String::from_utf8(encode(OutputFormat::Obj, &mesh, &params)).unwrap();
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), mesh.vertex_count());
+        let faces: Vec<&str> = obj.lines().filter(|line| line.starts_with("f ")).collect();
+        assert_eq!(faces.len(), mesh.triangle_count());
+        // Indices start at 1 and stay within the vertex list
+        let indices = faces.iter().flat_map(|face| face.split(' ').skip(1).map(|i| i.parse::<usize>().unwrap()));
+        assert!(indices.clone().all(|i| (1..=mesh.vertex_count()).contains(&i)));
+        assert_eq!(indices.min(), Some(1));
+    }
+
+    #[test]
+    fn ply_header_matches_the_body() {
+        let (params, mesh) = ring();
+        let (vertices, faces) = (mesh.vertex_count(), mesh.triangle_count());
+        let header = format!(
+            "ply\nformat {{}} 1.0\ncomment Optics Ring Generator {}, units: mm\n\
+             element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+             element face {}\nproperty list uchar uint vertex_indices\nend_header\n",
+            params.file_stem(), vertices, faces
+        );
+
+        let ascii = String::from_utf8(encode(OutputFormat::PlyAscii, &mesh, &params)).unwrap();
+        let body = ascii.strip_prefix(&header.replace("{}", "ascii")).expect("ASCII header");
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), vertices + faces);
+        assert!(lines[..vertices].iter().all(|line| line.split(' ').count() == 3));
+        assert!(lines[vertices..].iter().all(|line| line.starts_with("3 ") && line.split(' ').count() == 4));
+
+        let binary = encode(OutputFormat::PlyBinary, &mesh, &params);
+        let header = header.replace("{}", "binary_little_endian");
+        assert!(binary.starts_with(header.as_bytes()));
+        assert_eq!(binary.len() - header.len(), vertices * 12 + faces * 13);
+    }
+
+    #[test]
+    fn glb_chunks_are_aligned_and_sized() {
+        let (params, mesh) = ring();
+        let glb = encode(OutputFormat::Glb, &mesh, &params);
+        let word = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap()) as usize;
+
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(word(4), 2);
+        assert_eq!(word(8), glb.len());
+
+        let json_length = word(12);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(json_length % 4, 0);
+        let bin_start = 20 + json_length;
+        let bin_length = word(bin_start);
+        assert_eq!(&glb[bin_start + 4..bin_start + 8], b"BIN\0");
+        assert_eq!(bin_length % 4, 0);
+        assert_eq!(bin_start + 8 + bin_length, glb.len());
+
+        let json: serde_json::Value = serde_json::from_slice(&glb[20..bin_start]).unwrap();
+        assert_eq!(json["accessors"][0]["count"], mesh.vertex_count());
+        assert_eq!(json["accessors"][1]["count"], mesh.triangle_count() * 3);
+        let buffer_length = mesh.vertex_count() * 12 + mesh.triangle_count() * 12;
+        assert_eq!(json["buffers"][0]["byteLength"].as_u64().unwrap() as usize, buffer_length);
+    }
+
+    #[test]
+    fn mesh_files_are_streamed_into_place() {
+        let (params, mesh) = ring();
+        let dir = std::env::temp_dir().join(format!("optics-ring-writer-{}", std::process::id()));
+        let dir_name = dir.to_string_lossy().to_string();
+        for format in [OutputFormat::Stl, OutputFormat::Obj, OutputFormat::PlyBinary] {
+            let path = save_mesh_file(&mesh, &params, Some(&dir_name), format).unwrap();
+            assert_eq!(std::fs::read(&path).unwrap(), encode(format, &mesh, &params)[..], "{}", format);
+        }
+        let leftovers = std::fs::read_dir(&dir).unwrap().filter(|entry| {
+            entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".part")
+        });
+        assert_eq!(leftovers.count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-    
-    // Material recommendations
-    println!("  - Material: PLA or PETG for optical applications");
-    println!("  - Infill: 100% for maximum stability");
 }
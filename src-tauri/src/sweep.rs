@@ -0,0 +1,467 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+
+use crate::batch::{batch_report, run_parallel, BatchProgress, BatchReport, CancelToken};
+use crate::geometry::{generate_ring_mesh, RingParameters};
+use crate::label::{label_mesh, label_width};
+use crate::mesh::Mesh;
+use crate::stl_output::{save_plate_stl, write_mesh_file, OutputFormat};
+
+/// Most rings a single sweep may generate
+const MAX_SWEEP_RINGS: usize = 1000;
+/// Space between the cells of a plate (mm)
+const PLATE_GAP: f32 = 5.0;
+/// Height of the raised label digits on a plate (mm)
+const LABEL_HEIGHT: f32 = 5.0;
+/// How far the label digits stand up from the bed (mm)
+const LABEL_DEPTH: f32 = 0.8;
+/// Space between a ring and its label (mm)
+const LABEL_GAP: f32 = 2.0;
+
+/// Ring parameter that a sweep varies
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SweepField {
+    OuterDiameter,
+    InnerDiameter,
+    Height,
+    TopStepHeight,
+    BottomStepHeight,
+    MiddleStepHeight,
+    InnerStepWidth,
+    ChamferDepth,
+    LensRadius,
+    ContactDiameter,
+    ContactOffset,
+    PadRadius,
+    PadWidth,
+    PadHeight,
+}
+
+/// Request field name and the short key used in file names and STL headers
+const SWEEP_FIELDS: [(SweepField, &str, &str); 14] = [
+    (SweepField::OuterDiameter, "outer_diameter", "OD"),
+    (SweepField::InnerDiameter, "inner_diameter", "ID"),
+    (SweepField::Height, "height", "H"),
+    (SweepField::TopStepHeight, "top_step_height", "T"),
+    (SweepField::BottomStepHeight, "bottom_step_height", "B"),
+    (SweepField::MiddleStepHeight, "middle_step_height", "M"),
+    (SweepField::InnerStepWidth, "inner_step_width", "W"),
+    (SweepField::ChamferDepth, "chamfer_depth", "C"),
+    (SweepField::LensRadius, "lens_radius", "LR"),
+    (SweepField::ContactDiameter, "contact_diameter", "CD"),
+    (SweepField::ContactOffset, "contact_offset", "A"),
+    (SweepField::PadRadius, "pad_radius", "PR"),
+    (SweepField::PadWidth, "pad_width", "PW"),
+    (SweepField::PadHeight, "pad_height", "PH"),
+];
+
+impl SweepField {
+    /// Short key, e.g. `OD`
+    pub fn key(self) -> &'static str {
+        SWEEP_FIELDS.iter().find(|(field, _, _)| *field == self).map_or("", |(_, _, key)| key)
+    }
+
+    /// Set this field of the parameters, without validating them
+    fn apply(self, params: &mut RingParameters, value: f32) -> Result<()> {
+        let seat = &mut params.lens_seat;
+        match self {
+            SweepField::OuterDiameter => params.outer_diameter = value,
+            SweepField::InnerDiameter => params.inner_diameter = value,
+            // Step levels scale with the height, as for a new ring
+            SweepField::Height => *params = params.clone().with_height(value),
+            SweepField::TopStepHeight => params.top_step_height = value,
+            SweepField::BottomStepHeight => params.bottom_step_height = value,
+            SweepField::MiddleStepHeight => params.middle_step_height = value,
+            SweepField::InnerStepWidth => params.inner_step_width = value,
+            SweepField::ChamferDepth => params.chamfer_depth = value,
+            SweepField::LensRadius | SweepField::ContactDiameter => {
+                let seat = seat.as_mut().ok_or_else(|| anyhow!("The base ring has no lens seat to sweep"))?;
+                match self {
+                    SweepField::LensRadius => seat.radius_of_curvature = value,
+                    _ => seat.contact_diameter = Some(value),
+                }
+            }
+            SweepField::ContactOffset => params.contact_offset_deg = value,
+            SweepField::PadRadius => params.pad_radius = Some(value),
+            SweepField::PadWidth => params.pad_width = value,
+            SweepField::PadHeight => params.pad_height = value,
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for SweepField {
+    type Err = anyhow::Error;
+
+    /// Request field name (`inner_diameter` or `inner-diameter`) or short key (`ID`)
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().replace('-', "_");
+        SWEEP_FIELDS
+            .iter()
+            .find(|(_, field_name, key)| name.eq_ignore_ascii_case(field_name) || name.eq_ignore_ascii_case(key))
+            .map(|(field, _, _)| *field)
+            .ok_or_else(|| anyhow!("Cannot sweep '{}'. Sweepable fields are: {}", s, SWEEP_FIELDS.map(|(_, name, _)| name).join(", ")))
+    }
+}
+
+impl TryFrom<String> for SweepField {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// One swept field: values from `start` to `stop` inclusive in `step` increments
+#[derive(Debug, Clone, Deserialize)]
+pub struct SweepAxis {
+    pub field: SweepField,
+    pub start: f32,
+    pub stop: f32,
+    pub step: f32,
+}
+
+impl SweepAxis {
+    pub fn values(&self) -> Result<Vec<f32>> {
+        if self.step.is_nan() || self.step <= 0.0 || !self.start.is_finite() || !self.stop.is_finite() {
+            bail!("The {} sweep needs a positive step", self.field.key());
+        }
+        if self.stop < self.start {
+            bail!("The {} sweep stops before it starts", self.field.key());
+        }
+        // A stop that the steps miss by rounding only is still included
+        let (start, step) = (self.start as f64, self.step as f64);
+        let count = ((self.stop as f64 - start) / step + 1e-3).floor() as usize + 1;
+        if count > MAX_SWEEP_RINGS {
+            bail!("The {} sweep has {} values, more than {}", self.field.key(), count, MAX_SWEEP_RINGS);
+        }
+        // Rounded so 24.8 + 3 × 0.1 names a 25.1 ring, not 25.100002
+        Ok((0..count)
+            .map(|i| ((start + i as f64 * step) * 1e4).round() as f32 / 1e4)
+            .collect())
+    }
+}
+
+impl SweepAxis {
+    /// Decimals that show every value of the axis, e.g. 1 for 0.1mm steps
+    fn decimals(&self) -> usize {
+        (0..4)
+            .find(|&decimals| {
+                let scale = 10f64.powi(decimals as i32);
+                [self.start, self.step].iter().all(|&v| ((v as f64 * scale) - (v as f64 * scale).round()).abs() < 1e-3)
+            })
+            .unwrap_or(4)
+    }
+}
+
+impl std::str::FromStr for SweepAxis {
+    type Err = anyhow::Error;
+
+    /// `field=start:stop:step`, e.g. `inner_diameter=24.8:25.4:0.1`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid sweep '{}', expected field=start:stop:step", s);
+        let (field, range) = s.split_once('=').ok_or_else(invalid)?;
+        let numbers = range
+            .split(':')
+            .map(|n| n.trim().parse::<f32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>>>()?;
+        let [start, stop, step] = numbers[..] else {
+            return Err(invalid());
+        };
+        Ok(SweepAxis { field: field.parse()?, start, stop, step })
+    }
+}
+
+/// How the rings of a sweep are saved
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SweepLayout {
+    /// One file per ring
+    #[default]
+    Files,
+    /// Every ring on a single STL plate, labelled with its swept values
+    Plate,
+}
+
+impl std::str::FromStr for SweepLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "files" => Ok(SweepLayout::Files),
+            "plate" => Ok(SweepLayout::Plate),
+            _ => Err(anyhow!("Invalid sweep layout: {}. Valid layouts are: files, plate", s)),
+        }
+    }
+}
+
+/// A family of rings: a base ring with one or two fields swept over a range
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    pub base: RingParameters,
+    pub axes: Vec<SweepAxis>,
+}
+
+/// One combination of swept values
+#[derive(Debug, Clone)]
+pub struct SweepRing {
+    /// Value of each axis, in axis order
+    pub values: Vec<f32>,
+    /// Ring file name without extension
+    pub file_stem: String,
+    /// Swept values as printed on a plate, e.g. `25.0 30`
+    pub label: String,
+    pub params: Result<RingParameters, String>,
+}
+
+impl Sweep {
+    /// Every combination, the first axis varying fastest. Combinations that
+    /// make an invalid ring are kept as errors so they show up in the report.
+    pub fn rings(&self) -> Result<Vec<SweepRing>> {
+        if self.axes.is_empty() || self.axes.len() > 2 {
+            bail!("A sweep varies one or two fields");
+        }
+        if self.axes.len() == 2 && self.axes[0].field == self.axes[1].field {
+            bail!("Both sweeps vary {}", self.axes[0].field.key());
+        }
+        for axis in &self.axes {
+            axis.field.apply(&mut self.base.clone(), axis.start)?;
+        }
+        let values = self.axes.iter().map(SweepAxis::values).collect::<Result<Vec<_>>>()?;
+        let total: usize = values.iter().map(Vec::len).product();
+        if total > MAX_SWEEP_RINGS {
+            bail!("The sweep makes {} rings, more than {}", total, MAX_SWEEP_RINGS);
+        }
+
+        // The file name already shows the inner diameter to 0.1mm; other swept
+        // fields, and finer inner diameters, are added as KEY<value>
+        let suffixed: Vec<bool> = self.axes.iter().zip(&values)
+            .map(|(axis, values)| {
+                axis.field != SweepField::InnerDiameter
+                    || values.iter().any(|v| format!("{:.1}", v).parse::<f32>() != Ok(*v))
+            })
+            .collect();
+
+        let combinations = (0..total).map(|index| {
+            let mut rest = index;
+            values.iter().map(|axis_values| {
+                let value = axis_values[rest % axis_values.len()];
+                rest /= axis_values.len();
+                value
+            }).collect::<Vec<f32>>()
+        });
+        Ok(combinations
+            .map(|values| {
+                let mut params = self.base.clone();
+                let applied = self.axes.iter().zip(&values)
+                    .try_for_each(|(axis, &value)| axis.field.apply(&mut params, value));
+
+                let mut file_stem = params.file_stem();
+                for ((axis, value), suffixed) in self.axes.iter().zip(&values).zip(&suffixed) {
+                    if *suffixed {
+                        file_stem.push_str(&format!("-{}{}", axis.field.key(), value));
+                    }
+                }
+                let label = self.axes.iter().zip(&values)
+                    .map(|(axis, value)| format!("{:.*}", axis.decimals(), value))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let params = applied
+                    .and_then(|_| params.validate().map(|_| params))
+                    .map_err(|e| format!("Invalid parameters: {}", e));
+                SweepRing { values, file_stem, label, params }
+            })
+            .collect())
+    }
+
+    /// Plate file name without extension: the ring type and the swept ranges,
+    /// e.g. `CX-ID24.8-25.4-plate`
+    fn plate_file_stem(&self) -> Result<String> {
+        let mut file_stem = self.base.type_code();
+        for axis in &self.axes {
+            let values = axis.values()?;
+            let (first, last) = (values[0], values[values.len() - 1]);
+            file_stem.push_str(&format!("-{}{:.*}-{:.*}", axis.field.key(), axis.decimals(), first, axis.decimals(), last));
+        }
+        file_stem.push_str("-plate");
+        Ok(file_stem)
+    }
+
+    /// Short description for file headers, e.g. `ID 24.8..25.4 step 0.1`
+    fn description(&self) -> String {
+        self.axes.iter()
+            .map(|axis| format!("{} {}..{} step {}", axis.field.key(), axis.start, axis.stop, axis.step))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Generate every ring of a sweep on the batch worker pool. Separate files are
+/// written in `format` and named after each ring; a plate is a single binary
+/// STL with the rings in a grid and their swept values raised next to them.
+pub fn run_sweep(
+    sweep: &Sweep,
+    layout: SweepLayout,
+    format: OutputFormat,
+    output_dir: Option<&str>,
+    cancel: &CancelToken,
+    progress: impl FnMut(BatchProgress),
+) -> Result<BatchReport> {
+    if layout == SweepLayout::Plate && format != OutputFormat::Stl {
+        bail!("A sweep plate is saved as binary STL only");
+    }
+    let rings = sweep.rings()?;
+    let names = rings.iter().map(|ring| Some(ring.file_stem.clone())).collect();
+
+    let ring_mesh = |index: usize| {
+        let params = rings[index].params.as_ref().map_err(Clone::clone)?;
+        let mesh = generate_ring_mesh(params).map_err(|e| format!("Failed to generate mesh: {}", e))?;
+        Ok::<_, String>((params, mesh))
+    };
+
+    let results = match layout {
+        SweepLayout::Files => run_parallel(rings.len(), cancel, progress, |index| {
+            let (params, mesh) = ring_mesh(index)?;
+            let filename = format!("{}.{}", rings[index].file_stem, format.extension());
+            let path = write_mesh_file(&mesh, params, &filename, output_dir, format)
+                .map_err(|e| format!("Failed to write ring: {}", e))?;
+            Ok((path, mesh.triangle_count()))
+        }),
+        SweepLayout::Plate => {
+            let meshes = run_parallel(rings.len(), cancel, progress, |index| {
+                let (_, mesh) = ring_mesh(index)?;
+                let triangles = mesh.triangle_count();
+                Ok((mesh, triangles))
+            });
+            // A cancelled plate is not written at all
+            if cancel.is_cancelled() {
+                meshes.iter().map(|_| Err("Cancelled".to_string())).collect()
+            } else {
+                let columns = match sweep.axes.len() {
+                    1 => (rings.len() as f32).sqrt().ceil() as usize,
+                    _ => sweep.axes[0].values()?.len(),
+                };
+                let plate = plate_mesh(&rings, &meshes, columns)?;
+                let header = format!("Optics Ring Generator plate of {} rings: {}", rings.len(), sweep.description());
+                let filename = format!("{}.stl", sweep.plate_file_stem()?);
+                let path = save_plate_stl(&plate, &header, &filename, output_dir)?;
+                meshes.into_iter().map(|result| result.map(|(_, triangles)| (path.clone(), triangles))).collect()
+            }
+        }
+    };
+    Ok(batch_report(results, names, cancel))
+}
+
+/// Rings in a grid on z = 0, `columns` wide, each with its label in front of it.
+/// Rings that failed leave their cell empty.
+fn plate_mesh(rings: &[SweepRing], meshes: &[Result<(Mesh, usize), String>], columns: usize) -> Result<Mesh> {
+    let diameter = rings.iter()
+        .filter_map(|ring| ring.params.as_ref().ok())
+        .map(|params| params.outer_diameter)
+        .fold(0.0, f32::max);
+    let label_length = rings.iter().map(|ring| label_width(&ring.label, LABEL_HEIGHT)).fold(0.0, f32::max);
+    let cell_width = diameter.max(label_length) + PLATE_GAP;
+    let cell_height = diameter + LABEL_GAP + LABEL_HEIGHT + PLATE_GAP;
+
+    let mut plate = Mesh::new();
+    for (index, (ring, mesh)) in rings.iter().zip(meshes).enumerate() {
+        let Ok((mesh, _)) = mesh else { continue };
+        let (column, row) = (index % columns, index / columns);
+        let center = [(column as f32 + 0.5) * cell_width, -(row as f32 * cell_height) - diameter / 2.0];
+        let bottom = mesh.vertices.iter().map(|v| v[2]).fold(f32::MAX, f32::min);
+        plate.append(mesh, [center[0], center[1], -bottom]);
+
+        let label = &ring.label;
+        let origin = [
+            center[0] - label_width(label, LABEL_HEIGHT) / 2.0,
+            center[1] - diameter / 2.0 - LABEL_GAP - LABEL_HEIGHT,
+        ];
+        plate.append(&label_mesh(label, origin, LABEL_HEIGHT, LABEL_DEPTH)?, [0.0; 3]);
+    }
+    Ok(plate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::RingType;
+
+    fn axis(text: &str) -> SweepAxis {
+        text.parse().unwrap()
+    }
+
+    fn sweep(axes: &[&str]) -> Sweep {
+        Sweep {
+            base: RingParameters::new(RingType::Convex, 50.0, 25.0).unwrap(),
+            axes: axes.iter().map(|text| axis(text)).collect(),
+        }
+    }
+
+    #[test]
+    fn values_are_rounded_and_include_the_stop() {
+        let values = axis("ID=24.8:25.4:0.1").values().unwrap();
+        assert_eq!(values, [24.8, 24.9, 25.0, 25.1, 25.2, 25.3, 25.4]);
+
+        // 0.1 does not add up to 1.0 exactly in floating point
+        assert_eq!(axis("H=0:1:0.1").values().unwrap().len(), 11);
+        // A stop between two steps is not reached
+        assert_eq!(axis("H=10:20:3").values().unwrap(), [10.0, 13.0, 16.0, 19.0]);
+        assert_eq!(axis("H=10:10:1").values().unwrap(), [10.0]);
+    }
+
+    #[test]
+    fn bad_ranges_are_refused() {
+        let error = |text| axis(text).values().unwrap_err().to_string();
+        assert_eq!(error("ID=24:25:0"), "The ID sweep needs a positive step");
+        assert_eq!(error("ID=24:25:-0.1"), "The ID sweep needs a positive step");
+        assert_eq!(error("ID=25:24:0.1"), "The ID sweep stops before it starts");
+        assert!(error("ID=0:1000:0.1").starts_with("The ID sweep has 10001 values"));
+    }
+
+    #[test]
+    fn fields_parse_by_name_or_key() {
+        for text in ["inner_diameter", "inner-diameter", "INNER_DIAMETER", "ID", "id", " id "] {
+            assert_eq!(text.parse::<SweepField>().unwrap(), SweepField::InnerDiameter, "{}", text);
+        }
+        assert_eq!("PW".parse::<SweepField>().unwrap(), SweepField::PadWidth);
+        let error = "colour".parse::<SweepField>().unwrap_err().to_string();
+        assert!(error.starts_with("Cannot sweep 'colour'. Sweepable fields are: outer_diameter"), "{}", error);
+
+        let axis = axis("lens-radius = 80 : 100 : 5");
+        assert_eq!((axis.field, axis.start, axis.stop, axis.step), (SweepField::LensRadius, 80.0, 100.0, 5.0));
+        for text in ["ID=24:25", "ID:24:25:0.1", "ID=24:25:x", "ID=24:25:0.1:1"] {
+            assert!(text.parse::<SweepAxis>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn file_names_show_every_swept_value() {
+        let stems = |axes: &[&str]| -> Vec<String> {
+            sweep(axes).rings().unwrap().into_iter().map(|ring| ring.file_stem).collect()
+        };
+        // The file name already shows the inner diameter to 0.1mm
+        assert_eq!(stems(&["ID=24.8:25.0:0.1"]), ["CX-24.8", "CX-24.9", "CX-25.0"]);
+        // Finer steps would give two rings the same name without the suffix
+        assert_eq!(stems(&["ID=24.8:24.9:0.05"]), ["CX-24.8-ID24.8", "CX-24.9-ID24.85", "CX-24.9-ID24.9"]);
+        assert_eq!(stems(&["H=10:15:5", "OD=50:52:2"]), ["CX-25.0-H10-OD50", "CX-25.0-H15-OD50", "CX-25.0-H10-OD52", "CX-25.0-H15-OD52"]);
+    }
+
+    #[test]
+    fn plate_is_named_after_the_swept_ranges() {
+        assert_eq!(sweep(&["ID=24.8:25.4:0.1"]).plate_file_stem().unwrap(), "CX-ID24.8-25.4-plate");
+        assert_eq!(sweep(&["H=10:20:3", "LR=80:100:2.5"]).plate_file_stem().unwrap(), "CX-H10-19-LR80.0-100.0-plate");
+    }
+
+    #[test]
+    fn invalid_combinations_are_kept_as_errors() {
+        let rings = sweep(&["ID=40:50:5"]).rings().unwrap();
+        assert!(rings[0].params.is_ok());
+        assert!(rings[2].params.as_ref().unwrap_err().starts_with("Invalid parameters:"));
+        assert_eq!(rings[2].label, "50");
+
+        let error = sweep(&["LR=80:100:5"]).rings().unwrap_err();
+        assert_eq!(error.to_string(), "The base ring has no lens seat to sweep");
+        let error = sweep(&["ID=24:25:1", "id=30:31:1"]).rings().unwrap_err();
+        assert_eq!(error.to_string(), "Both sweeps vary ID");
+    }
+}
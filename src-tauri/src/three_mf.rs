@@ -0,0 +1,136 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::{Seek, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::geometry::RingParameters;
+use crate::mesh::Mesh;
+use crate::thumbnail::render_thumbnail;
+use crate::xml::xml_escape;
+
+/// Namespace of the ring parameter metadata entries
+const RING_NAMESPACE: &str = "urn:optics-ring-generator:ring";
+/// Edge length of the package thumbnail (pixels)
+const THUMBNAIL_SIZE: u32 = 256;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>
+  <Default Extension="png" ContentType="image/png"/>
+</Types>
+"#;
+
+const RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>
+  <Relationship Target="/Metadata/thumbnail.png" Id="rel1" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail"/>
+</Relationships>
+"#;
+
+/// Write the ring as a 3MF package: the mesh in millimetres, named after the
+/// part, with the ring parameters as metadata and a rendered thumbnail
+pub fn write_3mf<W: Write + Seek>(writer: W, mesh: &Mesh, params: &RingParameters) -> Result<()> {
+    let mut zip = ZipWriter::new(writer);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // PNG data is already compressed
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    zip.start_file("[Content_Types].xml", deflated)?;
+    zip.write_all(CONTENT_TYPES.as_bytes())?;
+
+    zip.start_file("_rels/.rels", deflated)?;
+    zip.write_all(RELATIONSHIPS.as_bytes())?;
+
+    zip.start_file("3D/3dmodel.model", deflated)?;
+    zip.write_all(model_xml(mesh, params).as_bytes())?;
+
+    zip.start_file("Metadata/thumbnail.png", stored)?;
+    zip.write_all(&render_thumbnail(mesh, THUMBNAIL_SIZE)?)?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// 3MF core model document for a single ring object
+fn model_xml(mesh: &Mesh, params: &RingParameters) -> String {
+    let name = xml_escape(&params.file_stem());
+    let mut xml = String::new();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<model unit=\"millimeter\" xml:lang=\"en-US\" \
+         xmlns=\"http://schemas.microsoft.com/3dmanufacturing/core/2015/02\" xmlns:ring=\"{}\">",
+        RING_NAMESPACE
+    );
+    let _ = writeln!(xml, "  <metadata name=\"Title\">{}</metadata>", name);
+    let _ = writeln!(xml, "  <metadata name=\"Application\">Optics Ring Generator</metadata>");
+    for (key, value) in params.metadata() {
+        let _ = writeln!(xml, "  <metadata name=\"ring:{}\">{}</metadata>", key, xml_escape(&value));
+    }
+
+    xml.push_str("  <resources>\n");
+    let _ = writeln!(xml, "    <object id=\"1\" type=\"model\" name=\"{}\">", name);
+    xml.push_str("      <mesh>\n        <vertices>\n");
+    for [x, y, z] in &mesh.vertices {
+        let _ = writeln!(xml, "          <vertex x=\"{}\" y=\"{}\" z=\"{}\"/>", x, y, z);
+    }
+    xml.push_str("        </vertices>\n        <triangles>\n");
+    for [v1, v2, v3] in &mesh.indices {
+        let _ = writeln!(xml, "          <triangle v1=\"{}\" v2=\"{}\" v3=\"{}\"/>", v1, v2, v3);
+    }
+    xml.push_str("        </triangles>\n      </mesh>\n    </object>\n  </resources>\n");
+    xml.push_str("  <build>\n    <item objectid=\"1\"/>\n  </build>\n</model>\n");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{generate_ring_mesh, RingType};
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        archive.by_name(name).unwrap_or_else(|_| panic!("{} missing", name)).read_to_end(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn package_holds_model_metadata_and_thumbnail() {
+        let params = RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap();
+        let mesh = generate_ring_mesh(&params).unwrap();
+        let mut data = Cursor::new(Vec::new());
+        write_3mf(&mut data, &mesh, &params).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(data.into_inner())).unwrap();
+
+        let content_types = String::from_utf8(read_entry(&mut archive, "[Content_Types].xml")).unwrap();
+        assert!(content_types.contains(r#"Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml""#));
+        assert!(content_types.contains(r#"Extension="png" ContentType="image/png""#));
+
+        let relationships = String::from_utf8(read_entry(&mut archive, "_rels/.rels")).unwrap();
+        assert!(relationships.contains(r#"Target="/3D/3dmodel.model""#));
+        assert!(relationships.contains(
+            r#"Target="/Metadata/thumbnail.png" Id="rel1" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail""#
+        ));
+
+        let model = String::from_utf8(read_entry(&mut archive, "3D/3dmodel.model")).unwrap();
+        assert!(model.contains(r#"<model unit="millimeter""#));
+        assert!(model.contains(&format!(r#"<metadata name="Title">{}</metadata>"#, params.file_stem())));
+        for (key, value) in params.metadata() {
+            assert!(
+                model.contains(&format!(r#"<metadata name="ring:{}">{}</metadata>"#, key, value)),
+                "metadata {} missing", key
+            );
+        }
+        assert_eq!(model.matches("<vertex ").count(), mesh.vertex_count());
+        assert_eq!(model.matches("<triangle ").count(), mesh.triangle_count());
+
+        let thumbnail = read_entry(&mut archive, "Metadata/thumbnail.png");
+        assert_eq!(&thumbnail[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
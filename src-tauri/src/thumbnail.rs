@@ -0,0 +1,125 @@
+use anyhow::Result;
+use nalgebra::Vector3;
+
+use crate::mesh::Mesh;
+
+/// Fraction of the image left empty around the part
+const MARGIN: f32 = 0.08;
+/// Shaded part color (RGB)
+const PART_COLOR: [f32; 3] = [0.72, 0.76, 0.82];
+
+/// Render an isometric, flat-shaded view of the mesh as a square RGBA PNG
+/// with a transparent background
+pub fn render_thumbnail(mesh: &Mesh, size: u32) -> Result<Vec<u8>> {
+    // Orthographic camera looking down at the part from the front right
+    let eye = Vector3::new(1.0f32, -1.0, 1.0).normalize();
+    let right = Vector3::z().cross(&eye).normalize();
+    let up = eye.cross(&right);
+    let light = Vector3::new(0.4f32, -0.6, 1.0).normalize();
+
+    let projected: Vec<[f32; 3]> = mesh
+        .vertices
+        .iter()
+        .map(|&[x, y, z]| {
+            let p = Vector3::new(x, y, z);
+            [p.dot(&right), p.dot(&up), p.dot(&eye)]
+        })
+        .collect();
+
+    // Fit the projected part into the image, keeping its aspect ratio
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+    for p in &projected {
+        min_x = min_x.min(p[0]);
+        max_x = max_x.max(p[0]);
+        min_y = min_y.min(p[1]);
+        max_y = max_y.max(p[1]);
+    }
+    let extent = (max_x - min_x).max(max_y - min_y).max(f32::EPSILON);
+    let scale = size as f32 * (1.0 - 2.0 * MARGIN) / extent;
+    let center_x = (min_x + max_x) / 2.0;
+    let center_y = (min_y + max_y) / 2.0;
+    let to_pixel = |p: &[f32; 3]| {
+        [
+            size as f32 / 2.0 + (p[0] - center_x) * scale,
+            size as f32 / 2.0 - (p[1] - center_y) * scale,
+            p[2],
+        ]
+    };
+
+    let pixels = (size * size) as usize;
+    let mut depth = vec![f32::MIN; pixels];
+    let mut image = vec![0u8; pixels * 4];
+
+    for index in 0..mesh.triangle_count() {
+        let normal = mesh.face_normal(index);
+        if normal.dot(&eye) <= 0.0 {
+            continue;
+        }
+        let shade = 0.35 + 0.65 * normal.dot(&light).max(0.0);
+        let color = PART_COLOR.map(|c| (c * shade * 255.0).round() as u8);
+
+        let [a, b, c] = mesh.indices[index].map(|i| to_pixel(&projected[i as usize]));
+        let area = edge(&a, &b, &c);
+        if area.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let x0 = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+        let x1 = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(size - 1);
+        let y0 = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+        let y1 = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(size - 1);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let p = [x as f32 + 0.5, y as f32 + 0.5, 0.0];
+                let (w0, w1, w2) = (edge(&b, &c, &p) / area, edge(&c, &a, &p) / area, edge(&a, &b, &p) / area);
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+                let z = w0 * a[2] + w1 * b[2] + w2 * c[2];
+                let pixel = (y * size + x) as usize;
+                if z > depth[pixel] {
+                    depth[pixel] = z;
+                    image[pixel * 4..pixel * 4 + 4].copy_from_slice(&[color[0], color[1], color[2], 255]);
+                }
+            }
+        }
+    }
+
+    let mut png_data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png_data, size, size);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&image)?;
+
+    Ok(png_data)
+}
+
+/// Twice the signed area of triangle (a, b, p) in screen space
+fn edge(a: &[f32; 3], b: &[f32; 3], p: &[f32; 3]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{generate_ring_mesh, RingParameters, RingType};
+
+    #[test]
+    fn thumbnail_is_a_square_png_of_the_part() {
+        let mesh = generate_ring_mesh(&RingParameters::new(RingType::Convex, 50.0, 40.0).unwrap()).unwrap();
+        let data = render_thumbnail(&mesh, 64).unwrap();
+        assert_eq!(&data[..8], b"\x89PNG\r\n\x1a\n");
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(data)).read_info().unwrap();
+        let mut image = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut image).unwrap();
+        assert_eq!((info.width, info.height), (64, 64));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+
+        // Transparent margin around a shaded part
+        let alpha = |x: usize, y: usize| image[(y * 64 + x) * 4 + 3];
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(63, 63), 0);
+        assert!(image.chunks(4).any(|pixel| pixel[3] == 255));
+    }
+}
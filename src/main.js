@@ -4,7 +4,7 @@ import { OrbitControls } from 'three/addons/controls/OrbitControls.js';
 import { STLLoader } from 'three/addons/loaders/STLLoader.js';
 
 // Wait for Tauri to be ready and import APIs
-let invoke, open;
+let invoke, open, save, listen;
 
 async function initializeTauri() {
   try {
@@ -15,10 +15,13 @@ async function initializeTauri() {
     }
     
     const { invoke: tauriInvoke } = window.__TAURI__.core;
-    const { open: tauriOpen } = window.__TAURI__.dialog;
+    const { open: tauriOpen, save: tauriSave } = window.__TAURI__.dialog;
+    const { listen: tauriListen } = window.__TAURI__.event;
     
     invoke = tauriInvoke;
     open = tauriOpen;
+    save = tauriSave;
+    listen = tauriListen;
     
     console.log('Tauri APIs initialized successfully');
     return true;
@@ -43,6 +46,24 @@ let browseBtn;
 // Current selected output path
 let selectedOutputPath = null;
 
+// Optional inputs and the request field each one sets. Empty inputs, and inputs
+// hidden for the chosen ring type, are left out so the backend defaults apply.
+const OPTIONAL_FIELDS = [
+  { id: 'lens-radius', key: 'lens_radius', kind: 'number' },
+  { id: 'lens-surface', key: 'lens_surface', kind: 'text' },
+  { id: 'seat-type', key: 'seat_type', kind: 'text' },
+  { id: 'torus-radius', key: 'torus_radius', kind: 'number' },
+  { id: 'contact-diameter', key: 'contact_diameter', kind: 'number' },
+  { id: 'pad-width', key: 'pad_width', kind: 'number' },
+  { id: 'pad-height', key: 'pad_height', kind: 'number' },
+  { id: 'pad-tip', key: 'pad_tip', kind: 'text' },
+  { id: 'contact-count', key: 'contact_count', kind: 'integer' },
+  { id: 'contact-offset', key: 'contact_offset', kind: 'number' },
+  { id: 'pad-radius', key: 'pad_radius', kind: 'number' },
+  { id: 'chord-tolerance', key: 'chord_tolerance', kind: 'number' },
+  { id: 'angular-tolerance', key: 'angular_tolerance', kind: 'number' },
+];
+
 // 3D Preview variables
 let scene, camera, renderer, controls;
 let currentMesh = null;
@@ -81,6 +102,162 @@ async function selectOutputLocation() {
   }
 }
 
+// Fill the form from the parameters stored in an STL file generated earlier,
+// or from a ring spec file
+async function loadParameters() {
+  try {
+    const selected = await open({
+      multiple: false,
+      title: "Select an STL file generated by this app or a ring spec",
+      filters: [{ name: 'STL or ring spec', extensions: ['stl', 'toml', 'json'] }]
+    });
+    if (!selected) return;
+
+    const command = /\.(toml|json)$/i.test(selected) ? "read_ring_spec" : "read_ring_parameters";
+    const request = await invoke(command, { path: selected });
+
+    // Refuse rings the form would show as a different ring
+    const formKeys = new Set([
+      'ring_type', 'outer_diameter', 'inner_diameter', 'height', 'output_path', 'format', 'drawing',
+      ...OPTIONAL_FIELDS.map(({ key }) => key),
+    ]);
+    const unsupported = Object.keys(request).filter(key => request[key] !== null && !formKeys.has(key));
+    if (unsupported.length > 0) {
+      throw new Error(`The ring sets ${unsupported.join(', ')}, which the form has no inputs for; generate it with optics-ring-cli instead`);
+    }
+
+    document.getElementById('ring-type').value = request.ring_type;
+    updateRingTypeFields();
+    document.getElementById('outer-diameter').value = request.outer_diameter;
+    document.getElementById('inner-diameter').value = request.inner_diameter;
+    document.getElementById('ring-height').value = request.height ?? '';
+    for (const { id, key } of OPTIONAL_FIELDS) {
+      const input = document.getElementById(id);
+      input.value = request[key] ?? '';
+      // Selects fall back to their first option, the default
+      if (input.selectedIndex === -1) input.selectedIndex = 0;
+    }
+    if (request.format) {
+      document.getElementById('output-format').value = request.format;
+      document.getElementById('drawing-format').value = request.drawing ?? '';
+    }
+  } catch (error) {
+    showError(`${error}`);
+  }
+}
+
+// Save the form's ring with its output format and drawing as a spec file
+async function saveRingSpec() {
+  try {
+    const { ringType, innerDiameter, request } = validateForm();
+    // Named like the ring's own files, e.g. 6P-25.0 for a ring with six pads
+    const typeCode = ringType === '3P' ? `${request.contact_count ?? 3}P` : ringType;
+    const path = await save({
+      title: "Save ring spec",
+      defaultPath: `${typeCode}-${innerDiameter.toFixed(1)}.ring.toml`,
+      filters: [{ name: 'Ring spec', extensions: ['toml', 'json'] }]
+    });
+    if (!path) return;
+
+    const drawing = document.getElementById('drawing-format').value;
+    await invoke("save_ring_spec", {
+      request: {
+        ...request,
+        format: document.getElementById('output-format').value,
+        drawing: drawing || null,
+      },
+      path,
+      name: null,
+    });
+    showSuccess({ message: 'Ring spec saved', filename: path.split(/[\\/]/).pop(), file_path: path });
+  } catch (error) {
+    showError(`${error.message ?? error}`);
+  }
+}
+
+// Generate every ring of a CSV or JSON job file into the save location
+async function generateBatch() {
+  try {
+    const selected = await open({
+      multiple: false,
+      title: "Select a batch job file",
+      filters: [{ name: 'Batch job', extensions: ['csv', 'json'] }]
+    });
+    if (!selected) return;
+
+    const jobId = startJob();
+    const report = await invoke("generate_batch", {
+      jobId,
+      path: selected,
+      outputPath: outputPathInput.value || null,
+    });
+    finishJob();
+    showBatchReport(report);
+  } catch (error) {
+    finishJob();
+    showError(`${error}`);
+  }
+}
+
+// Generate a family of rings around the form's ring, one or two fields swept
+async function generateSweep() {
+  try {
+    const { request } = validateForm();
+    const sweep = [readSweepAxis(''), readSweepAxis('-2')].filter(axis => axis);
+    const layout = document.getElementById('sweep-layout').value;
+
+    const jobId = startJob();
+    const report = await invoke("generate_sweep", {
+      jobId,
+      request: {
+        ...request,
+        output_path: outputPathInput.value || null,
+        format: layout === 'plate' ? 'stl' : document.getElementById('output-format').value,
+      },
+      sweep,
+      layout,
+    });
+    finishJob();
+    showBatchReport(report);
+  } catch (error) {
+    finishJob();
+    showError(`${error.message ?? error}`);
+  }
+}
+
+// Field and range of a sweep axis, or null when no field is chosen
+function readSweepAxis(suffix) {
+  const field = document.getElementById(`sweep-field${suffix}`).value;
+  if (!field) return null;
+  const [start, stop, step] = ['start', 'stop', 'step']
+    .map(part => parseFloat(document.getElementById(`sweep-${part}${suffix}`).value));
+  if ([start, stop, step].some(isNaN)) {
+    throw new Error('Please enter the start, stop and step of the sweep');
+  }
+  return { field, start, stop, step };
+}
+
+// Summary of a batch or sweep with one line per failed row
+function showBatchReport(report) {
+  const total = report.rows.length;
+  successText.textContent = `Generated ${report.succeeded} of ${total} rings${report.cancelled ? ' (cancelled)' : ''}`;
+  filenameSpan.textContent = `${total} rows`;
+  filepathSpan.textContent = outputPathInput.value || 'Default location';
+  document.getElementById('drawing-detail').classList.add('hidden');
+
+  const container = document.getElementById('manufacturing-info');
+  container.replaceChildren();
+  for (const row of report.rows.filter(row => !row.success && row.error !== 'Cancelled')) {
+    const line = document.createElement('p');
+    line.className = 'issue-error';
+    line.textContent = `❌ Row ${row.row}${row.name ? ` (${row.name})` : ''}: ${row.error}`;
+    container.appendChild(line);
+  }
+
+  successMessage.classList.remove('hidden');
+  errorMessage.classList.add('hidden');
+}
+
 // Initialize 3D viewer
 function init3DViewer() {
   // Scene
@@ -125,7 +302,7 @@ function init3DViewer() {
 }
 
 // Generate and show 3D preview
-async function generate3DPreview(ringType, outerDiameter, innerDiameter) {
+async function generate3DPreview(request) {
   try {
     console.log('Generating 3D preview...');
     
@@ -133,19 +310,21 @@ async function generate3DPreview(ringType, outerDiameter, innerDiameter) {
     previewSection.classList.remove('hidden');
     
     // Get mesh data from Rust backend
-    const meshData = await invoke("generate_mesh_preview", {
-      request: {
-        ring_type: ringType,
-        outer_diameter: outerDiameter,
-        inner_diameter: innerDiameter,
-      }
-    });
+    const meshData = await invoke("generate_mesh_preview", { request });
 
     console.log('Mesh data received:', meshData);
 
     // Update info panel with model dimensions
     const modelDimensionsEl = document.getElementById('model-dimensions');
-    if (modelDimensionsEl) modelDimensionsEl.textContent = `${outerDiameter}mm × ${innerDiameter}mm`;
+    if (modelDimensionsEl) modelDimensionsEl.textContent = `${request.outer_diameter}mm × ${request.inner_diameter}mm`;
+
+    // Material usage measured from the mesh itself
+    const modelMaterialEl = document.getElementById('model-material');
+    invoke("get_mesh_stats", { request }).then(stats => {
+      if (modelMaterialEl) {
+        modelMaterialEl.textContent = `${(stats.volume / 1000).toFixed(2)}cm³, ${(stats.surface_area / 100).toFixed(1)}cm²`;
+      }
+    }).catch(error => console.warn('Failed to get mesh stats:', error));
 
     // Initialize 3D viewer
     init3DViewer();
@@ -247,7 +426,11 @@ async function generate3DPreview(ringType, outerDiameter, innerDiameter) {
         console.log('Created fallback test triangle');
       }
     }
-    geometry.computeVertexNormals();
+    if (meshData.normals && meshData.normals.length === meshData.vertices.length) {
+      geometry.setAttribute('normal', new THREE.BufferAttribute(new Float32Array(meshData.normals), 3));
+    } else {
+      geometry.computeVertexNormals();
+    }
 
     // Create material with better light interaction
     const material = new THREE.MeshPhongMaterial({ 
@@ -346,18 +529,53 @@ function onWindowResize() {
 }
 
 // Generate ring STL file
-async function generateRing(ringType, outerDiameter, innerDiameter, outputPath) {
+// Generation runs as a job on a worker thread; its progress events fill the
+// progress message until it finishes or is cancelled
+let currentJobId = null;
+
+function startJob() {
+  currentJobId = crypto.randomUUID();
+  showLoading();
+  document.getElementById('progress-text').textContent = 'Starting…';
+  document.getElementById('progress-bar').value = 0;
+  document.getElementById('progress-message').classList.remove('hidden');
+  return currentJobId;
+}
+
+function finishJob() {
+  currentJobId = null;
+  document.getElementById('progress-message').classList.add('hidden');
+}
+
+function showProgress(progress) {
+  if (progress.job_id !== currentJobId) return;
+  const bar = document.getElementById('progress-bar');
+  bar.max = progress.rings_total;
+  bar.value = progress.rings_done;
+  document.getElementById('progress-text').textContent =
+    `${progress.rings_done} of ${progress.rings_total} rings, ${progress.triangles_written.toLocaleString()} triangles written`;
+}
+
+async function cancelJob() {
+  if (!currentJobId) return;
+  document.getElementById('progress-text').textContent = 'Cancelling…';
+  await invoke("cancel_job", { jobId: currentJobId }).catch(console.error);
+}
+
+async function generateRing(request, outputPath, format, drawing) {
   try {
-    showLoading();
+    const jobId = startJob();
     
     const response = await invoke("generate_ring", {
+      jobId,
       request: {
-        ring_type: ringType,
-        outer_diameter: outerDiameter,
-        inner_diameter: innerDiameter,
+        ...request,
         output_path: outputPath,
+        format,
+        drawing: drawing || null,
       }
     });
+    finishJob();
 
     if (response.success) {
       showSuccess(response);
@@ -365,6 +583,7 @@ async function generateRing(ringType, outerDiameter, innerDiameter, outputPath)
       showError(response.message);
     }
   } catch (error) {
+    finishJob();
     showError(`Failed to generate ring: ${error}`);
   }
 }
@@ -381,11 +600,40 @@ function showSuccess(response) {
   successText.textContent = response.message;
   filenameSpan.textContent = response.filename || 'N/A';
   filepathSpan.textContent = response.file_path || 'N/A';
+  const drawingDetail = document.getElementById('drawing-detail');
+  document.getElementById('drawing-path').textContent = response.drawing_path || '';
+  drawingDetail.classList.toggle('hidden', !response.drawing_path);
+  showManufacturingReport(response.manufacturing);
   
   successMessage.classList.remove('hidden');
   errorMessage.classList.add('hidden');
 }
 
+// List printability issues and recommended print settings under the export result
+function showManufacturingReport(report) {
+  const container = document.getElementById('manufacturing-info');
+  if (!container) return;
+  container.replaceChildren();
+  if (!report) return;
+
+  const addLine = (text, className) => {
+    const line = document.createElement('p');
+    line.textContent = text;
+    if (className) line.className = className;
+    container.appendChild(line);
+  };
+
+  for (const issue of report.issues) {
+    addLine(`${issue.severity === 'error' ? '❌' : '⚠️'} ${issue.code}: ${issue.message}`, `issue-${issue.severity}`);
+  }
+  const settings = report.recommendations;
+  addLine(`Layer height: ${settings.layer_height}`);
+  addLine(`Supports: ${settings.supports}`);
+  addLine(`Orientation: ${settings.orientation}`);
+  addLine(`Material: ${settings.material}`);
+  addLine(`Infill: ${settings.infill}`);
+}
+
 function showError(message) {
   errorText.textContent = message;
   errorMessage.classList.remove('hidden');
@@ -396,6 +644,8 @@ function validateForm() {
   const ringType = document.getElementById('ring-type').value;
   const outerDiameter = parseFloat(document.getElementById('outer-diameter').value);
   const innerDiameter = parseFloat(document.getElementById('inner-diameter').value);
+  const heightValue = parseFloat(document.getElementById('ring-height').value);
+  const height = isNaN(heightValue) ? null : heightValue;
 
   if (!ringType) {
     throw new Error('Please select a ring type');
@@ -418,7 +668,54 @@ function validateForm() {
     throw new Error(`Wall thickness (${wallThickness.toFixed(2)}mm) is too thin. Minimum recommended: 1.0mm`);
   }
 
-  return { ringType, outerDiameter, innerDiameter };
+  if (height !== null && height <= 0) {
+    throw new Error('Please enter a valid ring height');
+  }
+
+  const request = {
+    ring_type: ringType,
+    outer_diameter: outerDiameter,
+    inner_diameter: innerDiameter,
+    height,
+    ...readOptionalFields(),
+  };
+
+  return { ringType, outerDiameter, innerDiameter, height, request };
+}
+
+function readOptionalFields() {
+  const fields = {};
+  for (const { id, key, kind } of OPTIONAL_FIELDS) {
+    const input = document.getElementById(id);
+    if (input.closest('[data-ring-types]')?.classList.contains('hidden') || input.value === '') continue;
+    if (kind === 'text') {
+      fields[key] = input.value;
+      continue;
+    }
+    const value = kind === 'integer' ? Number(input.value) : parseFloat(input.value);
+    if (isNaN(value) || (kind === 'integer' && !Number.isInteger(value))) {
+      const label = input.closest('.input-group').querySelector('.input-label').firstChild.textContent.trim();
+      throw new Error(`Please enter a valid ${label.toLowerCase()}`);
+    }
+    fields[key] = value;
+  }
+  return fields;
+}
+
+// Show only the inputs, and sweep fields, the chosen ring type uses
+function updateRingTypeFields() {
+  const ringType = document.getElementById('ring-type').value;
+  for (const element of document.querySelectorAll('[data-ring-types]')) {
+    const unused = !element.dataset.ringTypes.split(' ').includes(ringType);
+    if (element instanceof HTMLOptionElement) {
+      // Not every webview hides options, so they are disabled too
+      element.hidden = unused;
+      element.disabled = unused;
+      if (unused && element.selected) element.parentElement.selectedIndex = 0;
+    } else {
+      element.classList.toggle('hidden', unused);
+    }
+  }
 }
 
 window.addEventListener("DOMContentLoaded", async () => {
@@ -474,19 +771,49 @@ window.addEventListener("DOMContentLoaded", async () => {
   
   console.log('Browse button event listener attached');
 
+  document.getElementById('ring-type').addEventListener('change', updateRingTypeFields);
+  updateRingTypeFields();
+
+  document.getElementById('load-stl-btn').addEventListener('click', (e) => {
+    e.preventDefault();
+    loadParameters();
+  });
+
+  document.getElementById('batch-btn').addEventListener('click', (e) => {
+    e.preventDefault();
+    generateBatch();
+  });
+
+  document.getElementById('save-spec-btn').addEventListener('click', (e) => {
+    e.preventDefault();
+    saveRingSpec();
+  });
+
+  document.getElementById('sweep-btn').addEventListener('click', (e) => {
+    e.preventDefault();
+    generateSweep();
+  });
+
+  document.getElementById('cancel-btn').addEventListener('click', (e) => {
+    e.preventDefault();
+    cancelJob();
+  });
+
+  listen('generation-progress', event => showProgress(event.payload)).catch(console.error);
+
   // Add form submit handler
   ringForm.addEventListener('submit', async (e) => {
     e.preventDefault();
     
     try {
-      const { ringType, outerDiameter, innerDiameter } = validateForm();
+      const { request } = validateForm();
       
       // Hide preview label and action panel during generation
       previewLabel.style.display = 'none';
       actionPanel.style.display = 'none';
       
       // First generate and show 3D preview
-      await generate3DPreview(ringType, outerDiameter, innerDiameter);
+      await generate3DPreview(request);
       
       // Show action panel after generation is complete
       actionPanel.style.display = 'block';
@@ -500,8 +827,10 @@ window.addEventListener("DOMContentLoaded", async () => {
   // Add save STL button handler
   saveStlBtn.addEventListener('click', async () => {
     try {
-      const { ringType, outerDiameter, innerDiameter } = validateForm();
-      await generateRing(ringType, outerDiameter, innerDiameter, selectedOutputPath);
+      const { request } = validateForm();
+      const format = document.getElementById('output-format').value;
+      const drawing = document.getElementById('drawing-format').value;
+      await generateRing(request, selectedOutputPath, format, drawing);
     } catch (error) {
       showError(error.message);
     }